@@ -0,0 +1,162 @@
+//! Baseline files of accepted findings, so only new findings are reported.
+
+use std::{collections::BTreeSet, path::Path};
+
+use log::info;
+use serde::{Deserialize, Serialize};
+
+use crate::report::{AnalysisReport, Finding};
+
+/// Current version of the baseline file format
+const VERSION: u32 = 1;
+
+/// Findings that have been accepted, so only new findings are reported
+#[derive(Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct Baseline {
+    pub version: u32,
+    /// The identities of the accepted findings
+    pub findings: BTreeSet<String>,
+}
+
+impl Baseline {
+    /// Creates a baseline accepting all the findings in the report
+    pub fn from_report(report: &AnalysisReport) -> Self {
+        Self {
+            version: VERSION,
+            findings: report.findings.iter().map(identity).collect(),
+        }
+    }
+
+    pub fn read(path: &Path) -> anyhow::Result<Self> {
+        let contents = std::fs::read_to_string(path)
+            .map_err(|e| anyhow::anyhow!("Failed to read baseline {:?}: {}", path, e))?;
+        let baseline: Baseline = serde_json::from_str(&contents)
+            .map_err(|e| anyhow::anyhow!("Failed to parse baseline {:?}: {}", path, e))?;
+        if baseline.version > VERSION {
+            return Err(anyhow::anyhow!(
+                "Baseline {:?} has unsupported version {}",
+                path,
+                baseline.version
+            ));
+        }
+        Ok(baseline)
+    }
+
+    pub fn write(&self, path: &Path) -> anyhow::Result<()> {
+        let mut contents = serde_json::to_string_pretty(self)?;
+        contents.push('\n');
+        std::fs::write(path, contents)?;
+        info!(
+            "Baseline with {} finding(s) written to {:?}",
+            self.findings.len(),
+            path
+        );
+        Ok(())
+    }
+
+    /// Removes the accepted findings from the report and records the accepted findings that are
+    /// no longer found, only the findings of the categories checked in the report can be fixed
+    pub fn apply(&self, report: &mut AnalysisReport) {
+        let found: BTreeSet<String> = report.findings.iter().map(identity).collect();
+        let before = report.findings.len();
+        report
+            .findings
+            .retain(|finding| !self.findings.contains(&identity(finding)));
+        report.baselined = before - report.findings.len();
+        let checked: Vec<&str> = report.checked.iter().map(|x| x.rule_id()).collect();
+        report.fixed = self
+            .findings
+            .difference(&found)
+            .filter(|x| x.split(':').next().is_some_and(|x| checked.contains(&x)))
+            .cloned()
+            .collect();
+    }
+}
+
+/// The stable identity of a finding, made up of the category, the path and the name of the item.
+///
+/// The line is left out so the identity doesn't change when unrelated lines are edited.
+pub fn identity(finding: &Finding) -> String {
+    let path = finding.path.to_string_lossy().replace('\\', "/");
+    match &finding.name {
+        Some(name) => format!("{}:{}:{}", finding.category.rule_id(), path, name),
+        None => format!("{}:{}", finding.category.rule_id(), path),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::report::Category;
+
+    fn finding(category: Category, path: &str, name: Option<&str>) -> Finding {
+        let finding = Finding::new(category, PathBuf::from(path), "");
+        match name {
+            Some(name) => finding.with_name(name),
+            None => finding,
+        }
+    }
+
+    #[test]
+    fn test_identity() {
+        assert_eq!(
+            identity(&finding(Category::UnreferencedFile, "lib/a.dart", None)),
+            "unreferenced-file:lib/a.dart"
+        );
+        assert_eq!(
+            identity(
+                &finding(Category::UnusedDependency, "pubspec.yaml", Some("http"))
+                    .with_line(Some(3))
+            ),
+            "unused-dependency:pubspec.yaml:http"
+        );
+    }
+
+    #[test]
+    fn test_apply() {
+        let old = AnalysisReport {
+            findings: vec![
+                finding(Category::UnreferencedFile, "lib/a.dart", None),
+                finding(Category::UnreferencedFile, "lib/b.dart", None),
+                finding(Category::UnusedDependency, "pubspec.yaml", Some("http")),
+            ],
+            ..Default::default()
+        };
+        let baseline = Baseline::from_report(&old);
+        let mut report = AnalysisReport {
+            findings: vec![
+                finding(Category::UnreferencedFile, "lib/a.dart", None),
+                finding(Category::UnreferencedFile, "lib/c.dart", None),
+            ],
+            // The dependencies aren't checked so the dependency isn't fixed
+            checked: vec![Category::UnreferencedFile],
+            ..Default::default()
+        };
+        baseline.apply(&mut report);
+        assert_eq!(report.findings.len(), 1);
+        assert_eq!(report.findings[0].path, PathBuf::from("lib/c.dart"));
+        assert_eq!(report.baselined, 1);
+        assert_eq!(
+            report.fixed,
+            vec!["unreferenced-file:lib/b.dart".to_string()]
+        );
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let report = AnalysisReport {
+            findings: vec![finding(
+                Category::UnusedLabel,
+                "lib/l10n/intl_en.arb",
+                Some("hello"),
+            )],
+            ..Default::default()
+        };
+        let baseline = Baseline::from_report(&report);
+        let json = serde_json::to_string(&baseline).unwrap();
+        let parsed: Baseline = serde_json::from_str(&json).unwrap();
+        assert_eq!(baseline, parsed);
+    }
+}
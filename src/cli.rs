@@ -1,5 +1,10 @@
 use std::path::PathBuf;
 
+use serde::Serialize;
+
+use crate::report::{Category, Check, Severity};
+
+#[derive(Debug, Clone, Default, Serialize)]
 pub struct Options {
     pub path: PathBuf,
     pub remove: bool,
@@ -8,7 +13,115 @@ pub struct Options {
     // pub dart: bool,
     pub labels: bool,
     pub loc: bool,
-    // pub format: bool,
-    // pub warn: bool,
+    /// Entry point patterns, overriding the ones in the config file
+    pub entries: Vec<String>,
+    /// Report every finding as a warning so the run never fails because of them
+    pub warn: bool,
+    /// The checks whose findings fail the run, all of them when empty
+    pub fail_on: Vec<Check>,
+    /// Baseline whose findings are left out of the report
+    pub baseline: Option<PathBuf>,
+    /// File to write the baseline of the current findings to
+    pub write_baseline: Option<PathBuf>,
+    /// Config file to use instead of the one in the project root
+    pub config: Option<PathBuf>,
+    /// Check the doc comments as well as the code, e.g. for assets only named in the docs
+    pub doc_comments: bool,
+    /// The `dart.library.*` conditions conditional imports are resolved for, overriding the ones
+    /// in the config file
+    pub conditions: Vec<String>,
+    /// Rewrite the directives whose path differs in case from the file
+    pub fix_case: bool,
     // pub output: bool,
 }
+
+impl Options {
+    /// The categories checked with these options, unreferenced files are always checked
+    pub fn checked_categories(&self) -> Vec<Category> {
+        Category::ALL
+            .into_iter()
+            .filter(|category| match category {
+                Category::UnreferencedAsset | Category::UnregisteredAsset => self.assets,
+                Category::UnusedDependency => self.deps,
+                Category::UnusedLabel => self.labels,
+                Category::UnusedLocator => self.loc,
+                Category::TestOnlyFile
+                | Category::UnreferencedFile
+                | Category::BrokenReference
+                | Category::CaseMismatch
+                | Category::PartMismatch
+                | Category::OrphanedPart
+                | Category::StaleGenerated => true,
+            })
+            .collect()
+    }
+
+    /// Adjusts the configured severity of a category for `warn` and `fail_on`, which can only
+    /// lower an error to a warning
+    pub fn severity(&self, severity: Severity, category: Category) -> Severity {
+        if severity == Severity::Error
+            && (self.warn || !(self.fail_on.is_empty() || self.fail_on.contains(&category.check())))
+        {
+            Severity::Warning
+        } else {
+            severity
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_severity_warn() {
+        let options = Options {
+            warn: true,
+            ..Default::default()
+        };
+        for category in Category::ALL {
+            assert_eq!(
+                options.severity(Severity::Error, category),
+                Severity::Warning
+            );
+        }
+    }
+
+    #[test]
+    fn test_severity_fail_on() {
+        let options = Options {
+            fail_on: vec![Check::Files, Check::Deps],
+            ..Default::default()
+        };
+        let error = |category| options.severity(Severity::Error, category);
+        assert_eq!(error(Category::UnreferencedFile), Severity::Error);
+        assert_eq!(error(Category::StaleGenerated), Severity::Error);
+        assert_eq!(error(Category::UnusedDependency), Severity::Error);
+        assert_eq!(error(Category::UnreferencedAsset), Severity::Warning);
+        assert_eq!(error(Category::UnusedLabel), Severity::Warning);
+        assert_eq!(error(Category::UnusedLocator), Severity::Warning);
+        // Every check fails the run without fail_on
+        let options = Options::default();
+        for category in Category::ALL {
+            assert_eq!(options.severity(Severity::Error, category), Severity::Error);
+        }
+    }
+
+    #[test]
+    fn test_severity_never_raised() {
+        let strict = Options::default();
+        let fail_on = Options {
+            fail_on: vec![Check::Labels],
+            ..Default::default()
+        };
+        let warn = Options {
+            warn: true,
+            ..Default::default()
+        };
+        for options in [strict, fail_on, warn] {
+            for severity in [Severity::Warning, Severity::Info, Severity::Off] {
+                assert_eq!(options.severity(severity, Category::UnusedLabel), severity);
+            }
+        }
+    }
+}
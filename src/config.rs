@@ -1,21 +1,576 @@
+use std::path::{Path, PathBuf};
+
+use log::info;
 use serde::{Deserialize, Serialize};
+use yaml_rust2::{Event, Yaml, YamlEmitter, parser::Parser, scanner::Marker};
+
+use crate::{
+    generated::GENERATED_SUFFIXES,
+    pubspec::PubspecSchema,
+    report::{Category, Severity},
+};
+
+/// Name of the config file looked for in the project root
+pub const CONFIG_FILE: &str = "unused.config.yaml";
+
+/// Loads the config of the project.
+///
+/// The config is taken from the first of these that exists:
+/// - the explicit config file given
+/// - `unused.config.yaml` in the project root
+/// - the `dart_unused` section of the pubspec
+///
+/// The configs it extends are merged in. This must be called after the current directory is set
+/// to the project root.
+pub fn load(explicit: Option<&Path>, pubspec: &PubspecSchema) -> anyhow::Result<Config> {
+    if let Some(path) = explicit {
+        return read(path);
+    }
+    let path = Path::new(CONFIG_FILE);
+    if path.is_file() {
+        return read(path);
+    }
+    if let Some(config) = &pubspec.dart_unused {
+        info!("Using the dart_unused section of pubspec.yaml as config");
+        return extend(config.clone(), Path::new("."), &mut Vec::new());
+    }
+    Ok(Config::default())
+}
+
+/// Reads and parses the config file along with the configs it extends
+pub fn read(path: &Path) -> anyhow::Result<Config> {
+    let config = read_file(path)?;
+    let dir = path.parent().unwrap_or(Path::new("."));
+    extend(config, dir, &mut vec![canonical(path)?])
+}
+
+fn read_file(path: &Path) -> anyhow::Result<Config> {
+    let contents = std::fs::read_to_string(path)
+        .map_err(|e| anyhow::anyhow!("Failed to read config {:?}: {}", path, e))?;
+    info!("Using config {:?}", path);
+    parse(&contents).map_err(|e| anyhow::anyhow!("Invalid config {:?}: {}", path, e))
+}
+
+/// Merges the config it extends into the config, `dir` is the directory `extends` is relative to
+/// and `chain` the configs already being read, used to detect cycles
+fn extend(config: Config, dir: &Path, chain: &mut Vec<PathBuf>) -> anyhow::Result<Config> {
+    let Some(extends) = &config.extends else {
+        return Ok(config);
+    };
+    let path = dir.join(extends);
+    let id = canonical(&path)?;
+    if chain.contains(&id) {
+        return Err(anyhow::anyhow!(
+            "Config {:?} extends itself through {:?}",
+            path,
+            chain
+        ));
+    }
+    chain.push(id);
+    let parent = read_file(&path)?;
+    let parent = extend(parent, path.parent().unwrap_or(Path::new(".")), chain)?;
+    Ok(parent.merge(config))
+}
+
+fn canonical(path: &Path) -> anyhow::Result<PathBuf> {
+    path.canonicalize()
+        .map_err(|e| anyhow::anyhow!("Failed to read config {:?}: {}", path, e))
+}
+
+/// Converts the JSON value of a config into YAML
+fn yaml(value: serde_json::Value) -> Yaml {
+    match value {
+        serde_json::Value::Null => Yaml::Null,
+        serde_json::Value::Bool(x) => Yaml::Boolean(x),
+        serde_json::Value::Number(x) => match x.as_i64() {
+            Some(x) => Yaml::Integer(x),
+            None => Yaml::Real(x.to_string()),
+        },
+        serde_json::Value::String(x) => Yaml::String(x),
+        serde_json::Value::Array(x) => Yaml::Array(x.into_iter().map(yaml).collect()),
+        serde_json::Value::Object(x) => Yaml::Hash(
+            x.into_iter()
+                .map(|(key, value)| (Yaml::String(key), yaml(value)))
+                .collect(),
+        ),
+    }
+}
 
-#[derive(Debug, Default, Deserialize, Serialize)]
+/// Appends the items of the child list missing from the parent list
+fn union(mut parent: Vec<String>, child: Vec<String>) -> Vec<String> {
+    for item in child {
+        if !parent.contains(&item) {
+            parent.push(item);
+        }
+    }
+    parent
+}
+
+/// Parses the contents of a config file, the errors include the line and column of the problem
+pub fn parse(contents: &str) -> anyhow::Result<Config> {
+    // an empty file has no document to deserialize
+    if contents.trim().is_empty() {
+        return Ok(Config::default());
+    }
+    serde_yaml2::from_str(contents).map_err(|e| with_position(contents, e.to_string()))
+}
+
+/// Adds the position of the unknown key or value named in a semantic error, such as
+/// ``unknown field `severty` ``, the syntax errors already include it. The line and column both
+/// start at 1
+pub(crate) fn with_position(contents: &str, error: String) -> anyhow::Error {
+    match locate(contents, &error) {
+        Some(marker) => anyhow::anyhow!(
+            "{} at position Line: {}, Column: {}",
+            error,
+            marker.line(),
+            // the marker column starts at 0, unlike its line
+            marker.col() + 1
+        ),
+        None => anyhow::anyhow!(error),
+    }
+}
+
+/// Finds the first mapping key or value the error is about
+fn locate(contents: &str, error: &str) -> Option<Marker> {
+    let (key, rest) = if let Some(rest) = error.strip_prefix("unknown field `") {
+        (true, rest)
+    } else if let Some(rest) = error.strip_prefix("unknown variant `") {
+        (false, rest)
+    } else {
+        return None;
+    };
+    let name = &rest[..rest.find('`')?];
+    // whether each open collection is a mapping expecting a key next, `None` for sequences
+    let mut stack: Vec<Option<bool>> = Vec::new();
+    let mut parser = Parser::new_from_str(contents);
+    loop {
+        let (event, marker) = parser.next_token().ok()?;
+        let expects_key = stack.last().copied().flatten();
+        if let Some(Some(expects_key)) = stack.last_mut()
+            && matches!(
+                event,
+                Event::Scalar(..)
+                    | Event::Alias(..)
+                    | Event::MappingStart(..)
+                    | Event::SequenceStart(..)
+            )
+        {
+            *expects_key = !*expects_key;
+        }
+        match event {
+            Event::Scalar(value, ..) if value == name && expects_key == Some(key) => {
+                return Some(marker);
+            }
+            Event::MappingStart(..) => stack.push(Some(true)),
+            Event::SequenceStart(..) => stack.push(None),
+            Event::MappingEnd | Event::SequenceEnd => {
+                stack.pop();
+            }
+            Event::StreamEnd => return None,
+            _ => {}
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize, Serialize, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Config {
+    /// Config file this config extends, relative to this config
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub extends: Option<String>,
+    /// Entry points of the app, relative to the project root and can be glob patterns
+    #[serde(default)]
+    pub entries: Vec<String>,
+    /// The conditions conditional imports are resolved for, e.g. `dart.library.io`, every URI is
+    /// followed when empty
+    #[serde(default)]
+    pub conditions: Vec<String>,
+    /// Deprecated, use `files.ignore` instead
     #[serde(default)]
     pub format_ignore: Vec<String>,
     #[serde(default)]
+    pub files: Files,
+    #[serde(default)]
     pub assets: Assets,
     #[serde(default)]
     pub deps: Deps,
+    #[serde(default)]
+    pub labels: Labels,
+    #[serde(default)]
+    pub locators: Locators,
+}
+
+impl Config {
+    /// Glob patterns of the Dart files left out of the unreferenced files, including the
+    /// deprecated `format_ignore` patterns
+    pub fn ignored_files(&self) -> Vec<String> {
+        self.format_ignore
+            .iter()
+            .chain(self.files.ignore.iter())
+            .cloned()
+            .collect()
+    }
+
+    /// Suffixes of the generated files, the common build_runner ones when none are set
+    pub fn generated_suffixes(&self) -> Vec<String> {
+        if self.files.generated.is_empty() {
+            GENERATED_SUFFIXES.iter().map(|x| x.to_string()).collect()
+        } else {
+            self.files.generated.clone()
+        }
+    }
+
+    /// The configured severity of the findings in the category
+    pub fn severity(&self, category: Category) -> Severity {
+        match category {
+            Category::UnreferencedFile => self.files.severity,
+            Category::TestOnlyFile => self.files.test_only_severity.or(self.files.severity),
+            Category::BrokenReference => self.files.broken_severity,
+            Category::CaseMismatch => self.files.case_severity,
+            Category::PartMismatch | Category::OrphanedPart => self.files.part_severity,
+            Category::StaleGenerated => self.files.generated_severity,
+            Category::UnreferencedAsset => self.assets.severity,
+            Category::UnregisteredAsset => self.assets.unregistered_severity,
+            Category::UnusedDependency => self.deps.severity,
+            Category::UnusedLabel => self.labels.severity,
+            Category::UnusedLocator => self.locators.severity,
+        }
+        .unwrap_or_default()
+    }
+
+    /// Writes the config as YAML
+    pub fn to_yaml(&self) -> anyhow::Result<String> {
+        let mut output = String::new();
+        YamlEmitter::new(&mut output).dump(&yaml(serde_json::to_value(self)?))?;
+        Ok(output)
+    }
+
+    /// Merges the child config into this config.
+    ///
+    /// The ignore and roots lists are combined, the entries and conditions of the child replace
+    /// the ones of this config when set and so do the severities.
+    pub fn merge(self, child: Config) -> Config {
+        Config {
+            extends: None,
+            entries: if child.entries.is_empty() {
+                self.entries
+            } else {
+                child.entries
+            },
+            conditions: if child.conditions.is_empty() {
+                self.conditions
+            } else {
+                child.conditions
+            },
+            format_ignore: union(self.format_ignore, child.format_ignore),
+            files: Files {
+                ignore: union(self.files.ignore, child.files.ignore),
+                roots: union(self.files.roots, child.files.roots),
+                generated: union(self.files.generated, child.files.generated),
+                severity: child.files.severity.or(self.files.severity),
+                test_only_severity: child
+                    .files
+                    .test_only_severity
+                    .or(self.files.test_only_severity),
+                broken_severity: child.files.broken_severity.or(self.files.broken_severity),
+                case_severity: child.files.case_severity.or(self.files.case_severity),
+                part_severity: child.files.part_severity.or(self.files.part_severity),
+                generated_severity: child
+                    .files
+                    .generated_severity
+                    .or(self.files.generated_severity),
+            },
+            assets: Assets {
+                ignore: union(self.assets.ignore, child.assets.ignore),
+                severity: child.assets.severity.or(self.assets.severity),
+                unregistered_severity: child
+                    .assets
+                    .unregistered_severity
+                    .or(self.assets.unregistered_severity),
+            },
+            deps: Deps {
+                ignore: union(self.deps.ignore, child.deps.ignore),
+                severity: child.deps.severity.or(self.deps.severity),
+            },
+            labels: Labels {
+                arb_dir: child.labels.arb_dir.or(self.labels.arb_dir),
+                class_name: child.labels.class_name.or(self.labels.class_name),
+                severity: child.labels.severity.or(self.labels.severity),
+            },
+            locators: Locators {
+                name: child.locators.name.or(self.locators.name),
+                severity: child.locators.severity.or(self.locators.severity),
+            },
+        }
+    }
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Files {
+    /// Glob patterns of the Dart files that are never reported as unreferenced or removed
+    #[serde(default)]
+    pub ignore: Vec<String>,
+    /// Glob patterns of the Dart files that are always live, these are walked like entry points
+    #[serde(default)]
+    pub roots: Vec<String>,
+    /// Suffixes of the generated files, e.g. `.g.dart`, each paired with the source file of the
+    /// same name ending with `.dart`
+    #[serde(default)]
+    pub generated: Vec<String>,
+    /// Severity of unreferenced files, defaults to error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Severity of the files only referenced from tests, defaults to `severity`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub test_only_severity: Option<Severity>,
+    /// Severity of the references to files that don't exist, defaults to error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub broken_severity: Option<Severity>,
+    /// Severity of the references whose path differs in case from the file, defaults to error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub case_severity: Option<Severity>,
+    /// Severity of the parts of another library and the orphaned parts, defaults to error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub part_severity: Option<Severity>,
+    /// Severity of the generated files whose source is gone, defaults to error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub generated_severity: Option<Severity>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Default, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Assets {
+    #[serde(default)]
     pub ignore: Vec<String>,
+    /// Severity of the registered assets that aren't referenced, defaults to error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+    /// Severity of the files in the asset directories that aren't registered, defaults to error
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unregistered_severity: Option<Severity>,
 }
 
-#[derive(Debug, Deserialize, Serialize, Default)]
+#[derive(Debug, Deserialize, Serialize, Default, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
 pub struct Deps {
+    /// Names of the dependencies that are never reported
+    #[serde(default)]
     pub ignore: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Labels {
+    /// Directory of the ARB files, defaults to the `arb_dir` of the flutter_intl section of the
+    /// pubspec, which is `lib/l10n` when it isn't set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arb_dir: Option<String>,
+    /// Class the localisations are retrieved from, defaults to the `class_name` of the
+    /// flutter_intl section of the pubspec, which is `AppLocalizations` when it isn't set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub class_name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+}
+
+#[derive(Debug, Deserialize, Serialize, Default, PartialEq, Eq, Clone)]
+#[serde(deny_unknown_fields)]
+pub struct Locators {
+    /// Name of the variable holding the GetIt instance, defaults to `locator`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub severity: Option<Severity>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse() {
+        let yaml = r#"
+entries:
+  - lib/main_*.dart
+format_ignore:
+  - "lib/**/*.g.dart"
+assets:
+  ignore:
+    - "assets/local/**/*.*"
+deps:
+  ignore:
+    - intl_utils
+"#;
+        let config = parse(yaml).unwrap();
+        assert_eq!(config.entries, vec!["lib/main_*.dart"]);
+        assert_eq!(config.format_ignore, vec!["lib/**/*.g.dart"]);
+        assert_eq!(config.ignored_files(), vec!["lib/**/*.g.dart"]);
+        assert_eq!(config.assets.ignore, vec!["assets/local/**/*.*"]);
+        assert_eq!(config.deps.ignore, vec!["intl_utils"]);
+    }
+
+    #[test]
+    fn test_files() {
+        let yaml = r#"
+format_ignore:
+  - "lib/**/*.g.dart"
+files:
+  ignore:
+    - "lib/**/*.freezed.dart"
+  roots:
+    - "lib/plugins/*.dart"
+"#;
+        let config = parse(yaml).unwrap();
+        assert_eq!(
+            config.ignored_files(),
+            vec!["lib/**/*.g.dart", "lib/**/*.freezed.dart"]
+        );
+        assert_eq!(config.files.roots, vec!["lib/plugins/*.dart"]);
+    }
+
+    #[test]
+    fn test_severity() {
+        let yaml = r#"
+files:
+  test_only_severity: warning
+assets:
+  severity: info
+  unregistered_severity: off
+labels:
+  severity: warning
+"#;
+        let config = parse(yaml).unwrap();
+        assert_eq!(config.severity(Category::UnreferencedFile), Severity::Error);
+        assert_eq!(config.severity(Category::TestOnlyFile), Severity::Warning);
+        assert_eq!(config.severity(Category::UnreferencedAsset), Severity::Info);
+        assert_eq!(config.severity(Category::UnregisteredAsset), Severity::Off);
+        assert_eq!(config.severity(Category::UnusedDependency), Severity::Error);
+        assert_eq!(config.severity(Category::UnusedLabel), Severity::Warning);
+        assert_eq!(config.severity(Category::UnusedLocator), Severity::Error);
+        assert!(parse("deps:\n  severity: fatal\n").is_err());
+    }
+
+    #[test]
+    fn test_merge() {
+        let parent = parse(
+            r#"
+entries: [lib/main.dart]
+assets:
+  ignore: [assets/a.png]
+deps:
+  ignore: [intl_utils]
+  severity: warning
+labels:
+  class_name: L10n
+  severity: info
+"#,
+        )
+        .unwrap();
+        let child = parse(
+            r#"
+extends: ../base.yaml
+deps:
+  ignore: [intl_utils, build_runner]
+labels:
+  severity: error
+"#,
+        )
+        .unwrap();
+        let config = parent.merge(child);
+        assert_eq!(config.extends, None);
+        assert_eq!(config.entries, vec!["lib/main.dart"]);
+        assert_eq!(config.assets.ignore, vec!["assets/a.png"]);
+        assert_eq!(config.deps.ignore, vec!["intl_utils", "build_runner"]);
+        assert_eq!(
+            config.severity(Category::UnusedDependency),
+            Severity::Warning
+        );
+        assert_eq!(config.severity(Category::UnusedLabel), Severity::Error);
+        assert_eq!(config.labels.class_name.as_deref(), Some("L10n"));
+    }
+
+    #[test]
+    fn test_generated_suffixes() {
+        assert_eq!(
+            Config::default().generated_suffixes().len(),
+            GENERATED_SUFFIXES.len()
+        );
+        let config = parse("files:\n  generated: ['.chopper.dart']\n").unwrap();
+        assert_eq!(config.generated_suffixes(), vec![".chopper.dart"]);
+    }
+
+    #[test]
+    fn test_extends() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        std::fs::create_dir_all(dir.join("app")).unwrap();
+        std::fs::write(dir.join("base.yaml"), "deps:\n  ignore: [intl_utils]\n").unwrap();
+        std::fs::write(
+            dir.join("app").join(CONFIG_FILE),
+            "extends: ../base.yaml\ndeps:\n  ignore: [build_runner]\n",
+        )
+        .unwrap();
+        let config = read(&dir.join("app").join(CONFIG_FILE)).unwrap();
+        assert_eq!(config.deps.ignore, vec!["intl_utils", "build_runner"]);
+
+        std::fs::write(dir.join("base.yaml"), "extends: app/unused.config.yaml\n").unwrap();
+        let error = read(&dir.join("app").join(CONFIG_FILE)).unwrap_err();
+        assert!(error.to_string().contains("extends itself"), "{}", error);
+    }
+
+    #[test]
+    fn test_to_yaml() {
+        let config = parse("deps:\n  ignore: [intl_utils]\n  severity: warning\n").unwrap();
+        assert_eq!(parse(&config.to_yaml().unwrap()).unwrap(), config);
+    }
+
+    #[test]
+    fn test_parse_empty() {
+        assert_eq!(parse("").unwrap(), Config::default());
+        assert_eq!(parse("deps: {}").unwrap(), Config::default());
+    }
+
+    #[test]
+    fn test_parse_error_position() {
+        let error = parse("assets:\n  ignore: 5\n").unwrap_err().to_string();
+        assert!(error.contains("Line: 2"), "{}", error);
+        let error = parse("entries: [a\n").unwrap_err().to_string();
+        assert!(error.contains("Line: 2"), "{}", error);
+    }
+
+    #[test]
+    fn test_semantic_error_position() {
+        let yaml = "deps:\n  ignore: [warn]\nlabels:\n  severity: warn\n";
+        let error = parse(yaml).unwrap_err().to_string();
+        assert!(error.starts_with("unknown variant `warn`"), "{}", error);
+        assert!(error.ends_with("Line: 4, Column: 13"), "{}", error);
+
+        let yaml = "files:\n  ignore: [severty]\n  severty: warning\n";
+        let error = parse(yaml).unwrap_err().to_string();
+        assert!(error.starts_with("unknown field `severty`"), "{}", error);
+        assert!(error.ends_with("Line: 3, Column: 3"), "{}", error);
+
+        let error = parse("entries: []\nlocator:\n  name: getIt\n")
+            .unwrap_err()
+            .to_string();
+        assert!(error.starts_with("unknown field `locator`"), "{}", error);
+        assert!(error.ends_with("Line: 2, Column: 1"), "{}", error);
+    }
+
+    #[test]
+    fn test_pubspec_section() {
+        let yaml = r#"
+name: app
+dart_unused:
+  entries:
+    - lib/main_dev.dart
+"#;
+        let pubspec: PubspecSchema = serde_yaml2::from_str(yaml).unwrap();
+        let config = pubspec.dart_unused.unwrap();
+        assert_eq!(config.entries, vec!["lib/main_dev.dart"]);
+    }
 }
@@ -0,0 +1,275 @@
+//! Finds the entry points the walk of the project starts from.
+
+use std::path::{Path, PathBuf};
+
+use glob::{Pattern, glob};
+use log::{debug, warn};
+
+use crate::{file_id::FileId, pubspec::PubspecSchema};
+
+/// Entry point used when none has been configured
+pub const DEFAULT_ENTRY: &str = "lib/main.dart";
+/// Entry points of the flavours of an app, used when there is no `lib/main.dart`
+pub const FLAVOUR_ENTRIES: &str = "lib/main_*.dart";
+
+/// Resolves the entry point patterns into the Dart files they match.
+///
+/// Patterns are relative to the project `root` and can either be plain paths or glob patterns.
+/// The CLI patterns take precedence over the ones in the config file, if neither are set the
+/// default `lib/main.dart` is used, or the `lib/main_*.dart` flavours when it doesn't exist.
+/// No entry points are found when nothing matches, as the project may still have other roots.
+pub fn resolve_entries(
+    root: &Path,
+    cli_entries: &[String],
+    config_entries: &[String],
+) -> anyhow::Result<Vec<PathBuf>> {
+    let patterns: Vec<String> = if !cli_entries.is_empty() {
+        cli_entries.to_vec()
+    } else if !config_entries.is_empty() {
+        config_entries.to_vec()
+    } else if root.join(DEFAULT_ENTRY).exists() {
+        vec![DEFAULT_ENTRY.to_string()]
+    } else {
+        vec![FLAVOUR_ENTRIES.to_string()]
+    };
+
+    let mut entries = Vec::with_capacity(patterns.len());
+    for pattern in patterns.iter() {
+        let matches: Vec<PathBuf> = glob(&in_root(root, pattern))?
+            .flatten()
+            .filter(|path| path.is_file())
+            .collect();
+        if matches.is_empty() {
+            warn!("Entry point {:?} did not match any files", pattern);
+        }
+        for path in matches {
+            let path = FileId::new(path).into_path();
+            if !entries.contains(&path) {
+                debug!("Using entry point {:?}", path);
+                entries.push(path);
+            }
+        }
+    }
+    Ok(entries)
+}
+
+/// Determines if the project is a package rather than an app.
+///
+/// A project is treated as a package when it is a Flutter plugin, or when it has neither a
+/// `lib/main.dart` nor any `lib/main_*.dart` flavours to use as entry points.
+pub fn is_package(root: &Path, pubspec: &PubspecSchema) -> bool {
+    pubspec.flutter.plugin.is_some() || !has_app_entries(root)
+}
+
+/// Checks if the project has a `lib/main.dart` or any `lib/main_*.dart` flavours
+fn has_app_entries(root: &Path) -> bool {
+    root.join(DEFAULT_ENTRY).is_file()
+        || glob(&in_root(root, FLAVOUR_ENTRIES))
+            .is_ok_and(|paths| paths.flatten().any(|x| x.is_file()))
+}
+
+/// Gets the public libraries of a package, which is every Dart file in `lib/` outside of `lib/src/`.
+///
+/// A package without any is only warned about, its executables may still be entry points.
+pub fn package_entries(root: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    let src = root.join("lib/src");
+    let mut entries: Vec<PathBuf> = glob(&in_root(root, "lib/**/*.dart"))?
+        .flatten()
+        .filter(|path| !path.starts_with(&src))
+        .collect();
+    entries.sort();
+    if entries.is_empty() {
+        warn!("No public libraries found in lib/");
+    }
+    for entry in entries.iter() {
+        debug!("Using public library {:?} as entry point", entry);
+    }
+    Ok(entries)
+}
+
+/// Patterns of the files outside `lib/` that are also entry points of the app
+const APP_ROOTS: [&str; 3] = ["bin/**/*.dart", "tool/**/*.dart", "web/main.dart"];
+
+/// Patterns of the test files, which are entry points for the tests
+const TEST_ROOTS: [&str; 2] = ["test/**/*_test.dart", "integration_test/**/*_test.dart"];
+
+/// Gets the executables, tools and web entry point of the project
+pub fn app_roots(root: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    find_roots(root, &APP_ROOTS)
+}
+
+/// Gets the files matching the `files.roots` patterns of the config, which are always live
+pub fn config_roots(root: &Path, patterns: &[String]) -> anyhow::Result<Vec<PathBuf>> {
+    find_roots(root, patterns)
+}
+
+/// Gets the test files in `test/` and `integration_test/`
+pub fn test_roots(root: &Path) -> anyhow::Result<Vec<PathBuf>> {
+    find_roots(root, &TEST_ROOTS)
+}
+
+fn find_roots(root: &Path, patterns: &[impl AsRef<str>]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut roots = Vec::new();
+    for pattern in patterns {
+        for path in glob(&in_root(root, pattern.as_ref()))?
+            .flatten()
+            .filter(|path| path.is_file())
+        {
+            debug!("Using {:?} as entry point", path);
+            roots.push(path);
+        }
+    }
+    roots.sort();
+    Ok(roots)
+}
+
+/// Makes a pattern relative to the project root relative to the current directory instead
+fn in_root(root: &Path, pattern: &str) -> String {
+    Path::new(&Pattern::escape(&root.to_string_lossy()))
+        .join(pattern)
+        .to_string_lossy()
+        .into_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Creates an empty file, along with the directories it is in
+    fn touch(dir: &Path, name: &str) -> PathBuf {
+        let path = dir.join(name);
+        std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+        std::fs::write(&path, "").unwrap();
+        path
+    }
+
+    fn patterns(patterns: &[&str]) -> Vec<String> {
+        patterns.iter().map(|x| x.to_string()).collect()
+    }
+
+    #[test]
+    fn test_resolve_entries() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let main = touch(dir, "lib/main.dart");
+        let dev = touch(dir, "lib/main_dev.dart");
+        let prod = touch(dir, "lib/main_prod.dart");
+        let book = touch(dir, "lib/widgetbook.dart");
+
+        assert_eq!(resolve_entries(dir, &[], &[]).unwrap(), vec![main]);
+        // The CLI entries take precedence over the config
+        assert_eq!(
+            resolve_entries(
+                dir,
+                &patterns(&["lib/widgetbook.dart"]),
+                &patterns(&["lib/main_*.dart"])
+            )
+            .unwrap(),
+            vec![book.clone()]
+        );
+        // Patterns are globbed and the files matched by several of them are only used once
+        let mut entries = resolve_entries(
+            dir,
+            &[],
+            &patterns(&[
+                "lib/main_*.dart",
+                "lib/main_dev.dart",
+                "lib/widgetbook.dart",
+            ]),
+        )
+        .unwrap();
+        entries.sort();
+        assert_eq!(entries, vec![dev.clone(), prod.clone(), book]);
+
+        assert!(
+            resolve_entries(dir, &patterns(&["lib/missing.dart"]), &[])
+                .unwrap()
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_resolve_flavours() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        assert!(resolve_entries(dir, &[], &[]).unwrap().is_empty());
+        let dev = touch(dir, "lib/main_dev.dart");
+        let prod = touch(dir, "lib/main_prod.dart");
+        let mut entries = resolve_entries(dir, &[], &[]).unwrap();
+        entries.sort();
+        assert_eq!(entries, vec![dev, prod]);
+    }
+
+    fn pubspec(contents: &str) -> PubspecSchema {
+        serde_yaml2::from_str(contents).unwrap()
+    }
+
+    #[test]
+    fn test_is_package() {
+        let app = pubspec("name: app\nflutter:\n  uses-material-design: true\n");
+        let plugin = pubspec(
+            "name: plugin\nflutter:\n  plugin:\n    platforms:\n      android:\n        pluginClass: Plugin\n",
+        );
+
+        // A Flutter library package has no entry point of its own
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        touch(dir, "lib/widgets.dart");
+        assert!(is_package(dir, &app));
+        touch(dir, "lib/main_dev.dart");
+        assert!(!is_package(dir, &app));
+        assert!(is_package(dir, &plugin));
+
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        touch(dir, "lib/main.dart");
+        assert!(!is_package(dir, &app));
+        assert!(is_package(dir, &plugin));
+    }
+
+    #[test]
+    fn test_package_entries() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        assert!(package_entries(dir).unwrap().is_empty());
+        let widgets = touch(dir, "lib/widgets.dart");
+        let buttons = touch(dir, "lib/widgets/buttons.dart");
+        touch(dir, "lib/src/button.dart");
+        touch(dir, "lib/src/theme/colors.dart");
+        touch(dir, "test/widgets_test.dart");
+        assert_eq!(package_entries(dir).unwrap(), vec![buttons, widgets]);
+    }
+
+    #[test]
+    fn test_app_and_test_roots() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let cli = touch(dir, "bin/cli.dart");
+        let release = touch(dir, "tool/release/main.dart");
+        let web = touch(dir, "web/main.dart");
+        touch(dir, "web/other.dart");
+        let unit = touch(dir, "test/unit/user_test.dart");
+        touch(dir, "test/helpers.dart");
+        let flow = touch(dir, "integration_test/flow_test.dart");
+
+        assert_eq!(app_roots(dir).unwrap(), vec![cli, release, web]);
+        assert_eq!(test_roots(dir).unwrap(), vec![flow, unit]);
+    }
+
+    #[test]
+    fn test_config_roots() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let maps = touch(dir, "lib/plugins/maps.dart");
+        touch(dir, "lib/plugins/README.md");
+        assert_eq!(
+            config_roots(dir, &patterns(&["lib/plugins/*.dart"])).unwrap(),
+            vec![maps]
+        );
+        assert!(
+            config_roots(dir, &patterns(&["lib/missing/*.dart"]))
+                .unwrap()
+                .is_empty()
+        );
+    }
+}
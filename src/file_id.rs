@@ -0,0 +1,306 @@
+//! Canonical identities of the files of the project, used by every set of paths.
+
+use std::{
+    collections::HashMap,
+    ffi::OsString,
+    fmt,
+    path::{Component, Path, PathBuf},
+};
+
+use serde::Serialize;
+
+use crate::parser::Uri;
+
+/// Canonical identity of a file of the project.
+///
+/// It is the path of the file relative to the project root with `.` and `..` resolved, so a file
+/// has the same id however it is reached, e.g. through `package:app/x.dart` or `../x.dart`.
+#[derive(Clone, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+pub struct FileId(PathBuf);
+
+impl FileId {
+    /// Creates the id of a path, either relative to the project root or absolute within it.
+    ///
+    /// This must be called after the current directory is set to the project root.
+    pub fn new(path: impl AsRef<Path>) -> Self {
+        let path = path.as_ref();
+        let path = if path.is_absolute() {
+            match std::env::current_dir() {
+                Ok(cwd) => path.strip_prefix(&cwd).unwrap_or(path),
+                Err(_) => path,
+            }
+        } else {
+            path
+        };
+        Self(normalise(path))
+    }
+
+    /// Resolves the URI of a directive in this file to the file of the project it refers to.
+    ///
+    /// URIs of other packages aren't files of the project and URIs that can't be decoded don't
+    /// refer to any file, so both are `None`.
+    pub fn resolve(&self, uri: &Uri, package_name: &str) -> Option<FileId> {
+        match uri {
+            Uri::Relative(path) => {
+                let path = percent_decode(path)?;
+                Some(Self(normalise(&self.0.parent()?.join(path))))
+            }
+            Uri::Package(name, path) if name == package_name => {
+                let path = percent_decode(path.trim_start_matches('/'))?;
+                Some(Self(normalise(&Path::new("lib").join(path))))
+            }
+            Uri::Package(..) => None,
+        }
+    }
+
+    /// Rewrites the URI of a directive in this file so it refers to `real`, the file it resolves
+    /// to with the casing on disk.
+    ///
+    /// Only the segments whose case differs are changed, so `..` segments and the package name
+    /// are kept as they are.
+    pub fn fix_case(&self, uri: &Uri, real: &FileId) -> Uri {
+        let (path, base) = match uri {
+            Uri::Relative(path) => (path, self.0.parent().unwrap_or(Path::new(""))),
+            Uri::Package(_, path) => (path, Path::new("lib")),
+        };
+        let mut segments: Vec<String> = path.split('/').map(|x| x.to_string()).collect();
+        // The components the URI resolves to along with the segment each one comes from
+        let mut resolved: Vec<(String, Option<usize>)> = base
+            .components()
+            .map(|x| (x.as_os_str().to_string_lossy().to_string(), None))
+            .collect();
+        for (index, segment) in segments.iter().enumerate() {
+            match segment.as_str() {
+                "" | "." => {}
+                ".." if resolved.last().is_some_and(|(x, _)| x != "..") => {
+                    resolved.pop();
+                }
+                _ => resolved.push((segment.clone(), Some(index))),
+            }
+        }
+        let components: Vec<_> = real.0.components().map(|x| x.as_os_str()).collect();
+        if components.len() == resolved.len() {
+            for ((_, index), component) in resolved.iter().zip(components) {
+                let (Some(index), Some(component)) = (index, component.to_str()) else {
+                    continue;
+                };
+                let segment = &segments[*index];
+                let decoded = percent_decode(segment).unwrap_or_else(|| segment.clone());
+                if decoded != component && decoded.to_lowercase() == component.to_lowercase() {
+                    segments[*index] = component.to_string();
+                }
+            }
+        }
+        match uri {
+            Uri::Relative(_) => Uri::Relative(segments.join("/")),
+            Uri::Package(name, _) => Uri::Package(name.clone(), segments.join("/")),
+        }
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.0
+    }
+
+    pub fn into_path(self) -> PathBuf {
+        self.0
+    }
+}
+
+impl AsRef<Path> for FileId {
+    fn as_ref(&self) -> &Path {
+        &self.0
+    }
+}
+
+/// Listings of the directories of the project, used to find the casing of the files on disk
+#[derive(Debug, Default)]
+pub struct DirEntries(HashMap<PathBuf, Vec<OsString>>);
+
+impl DirEntries {
+    /// Finds the file with the casing of the directory entries, so a file imported with the wrong
+    /// case is found on case sensitive and insensitive file systems alike.
+    ///
+    /// An exact match is preferred over one ignoring the case, `None` when no file matches.
+    pub fn on_disk(&mut self, file: &FileId) -> Option<FileId> {
+        let mut real = PathBuf::new();
+        for component in file.0.components() {
+            let Component::Normal(name) = component else {
+                real.push(component);
+                continue;
+            };
+            let entries = self
+                .0
+                .entry(real.clone())
+                .or_insert_with(|| list_dir(&real));
+            if entries.iter().any(|x| x == name) {
+                real.push(name);
+            } else {
+                let lower = name.to_string_lossy().to_lowercase();
+                let entry = entries
+                    .iter()
+                    .find(|x| x.to_string_lossy().to_lowercase() == lower)?;
+                real.push(entry);
+            }
+        }
+        real.is_file().then_some(FileId(real))
+    }
+}
+
+/// Gets the names of the entries of a directory relative to the project root
+fn list_dir(dir: &Path) -> Vec<OsString> {
+    let dir = if dir.as_os_str().is_empty() {
+        Path::new(".")
+    } else {
+        dir
+    };
+    std::fs::read_dir(dir)
+        .map(|entries| entries.flatten().map(|x| x.file_name()).collect())
+        .unwrap_or_default()
+}
+
+// Shown as the path so the logs read the same as for plain paths
+impl fmt::Debug for FileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        fmt::Debug::fmt(&self.0, f)
+    }
+}
+
+impl fmt::Display for FileId {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0.display())
+    }
+}
+
+/// Resolves the `.` and `..` components of the path without touching the file system, `..` at
+/// the start is kept as it is outside of the project
+fn normalise(path: &Path) -> PathBuf {
+    let mut normalised = PathBuf::new();
+    for component in path.components() {
+        match component {
+            Component::CurDir => {}
+            Component::ParentDir => {
+                if matches!(
+                    normalised.components().next_back(),
+                    Some(Component::Normal(_))
+                ) {
+                    normalised.pop();
+                } else {
+                    normalised.push("..");
+                }
+            }
+            component => normalised.push(component),
+        }
+    }
+    normalised
+}
+
+/// Decodes the percent encoded bytes of a URI path, `None` when the encoding is invalid or the
+/// decoded bytes aren't UTF-8
+pub fn percent_decode(value: &str) -> Option<String> {
+    if !value.contains('%') {
+        return Some(value.to_string());
+    }
+    let bytes = value.as_bytes();
+    let mut decoded = Vec::with_capacity(bytes.len());
+    let mut index = 0;
+    while index < bytes.len() {
+        if bytes[index] == b'%' {
+            let hex = std::str::from_utf8(bytes.get(index + 1..index + 3)?).ok()?;
+            decoded.push(u8::from_str_radix(hex, 16).ok()?);
+            index += 3;
+        } else {
+            decoded.push(bytes[index]);
+            index += 1;
+        }
+    }
+    String::from_utf8(decoded).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_new() {
+        assert_eq!(FileId::new("./lib/a.dart").path(), Path::new("lib/a.dart"));
+        assert_eq!(
+            FileId::new("lib/src/../a.dart").path(),
+            Path::new("lib/a.dart")
+        );
+        assert_eq!(FileId::new("../a.dart").path(), Path::new("../a.dart"));
+    }
+
+    #[test]
+    fn test_resolve() {
+        let file = FileId::new("lib/src/widgets/button.dart");
+        let relative = Uri::Relative("../x.dart".to_string());
+        let package = Uri::Package("app".to_string(), "/src/x.dart".to_string());
+        assert_eq!(
+            file.resolve(&relative, "app"),
+            file.resolve(&package, "app")
+        );
+        assert_eq!(
+            file.resolve(&relative, "app").unwrap().path(),
+            Path::new("lib/src/x.dart")
+        );
+        assert_eq!(file.resolve(&package, "other"), None);
+        let encoded = Uri::Relative("caf%C3%A9%20menu.dart".to_string());
+        assert_eq!(
+            file.resolve(&encoded, "app").unwrap().path(),
+            Path::new("lib/src/widgets/café menu.dart")
+        );
+    }
+
+    #[test]
+    fn test_fix_case() {
+        let file = FileId::new("lib/src/pages/home.dart");
+        let real = FileId::new("lib/src/widgets/button.dart");
+        assert_eq!(
+            file.fix_case(&Uri::Relative("../Widgets/Button.dart".to_string()), &real),
+            Uri::Relative("../widgets/button.dart".to_string())
+        );
+        assert_eq!(
+            file.fix_case(
+                &Uri::Package("app".to_string(), "/SRC/widgets/button.dart".to_string()),
+                &real
+            ),
+            Uri::Package("app".to_string(), "/src/widgets/button.dart".to_string())
+        );
+        assert_eq!(
+            file.fix_case(
+                &Uri::Relative("./../widgets/button.dart".to_string()),
+                &real
+            ),
+            Uri::Relative("./../widgets/button.dart".to_string())
+        );
+    }
+
+    #[test]
+    fn test_on_disk() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        std::fs::create_dir_all(dir.join("widgets")).unwrap();
+        std::fs::write(dir.join("widgets/button.dart"), "").unwrap();
+        let mut entries = DirEntries::default();
+        let real = FileId(dir.join("widgets/button.dart"));
+        assert_eq!(
+            entries.on_disk(&FileId(dir.join("Widgets/Button.dart"))),
+            Some(real.clone())
+        );
+        assert_eq!(entries.on_disk(&real), Some(real));
+        assert_eq!(
+            entries.on_disk(&FileId(dir.join("widgets/missing.dart"))),
+            None
+        );
+    }
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("a%20b").as_deref(), Some("a b"));
+        assert_eq!(percent_decode("%E2%9C%93.dart").as_deref(), Some("✓.dart"));
+        assert_eq!(percent_decode("plain.dart").as_deref(), Some("plain.dart"));
+        assert_eq!(percent_decode("a%2").as_deref(), None);
+        assert_eq!(percent_decode("a%zz").as_deref(), None);
+        assert_eq!(percent_decode("%FF").as_deref(), None);
+    }
+}
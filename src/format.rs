@@ -0,0 +1,81 @@
+//! Renders the report in the output format picked with `--format`.
+
+use crate::{cli::Options, report::AnalysisReport};
+
+pub mod checkstyle;
+pub mod json;
+pub mod junit;
+pub mod sarif;
+
+/// The formats the findings can be output in
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, clap::ValueEnum)]
+pub enum Format {
+    /// Human readable log output
+    #[default]
+    Text,
+    /// A single JSON document
+    Json,
+    /// SARIF 2.1.0 log for code scanning tools
+    Sarif,
+    /// JUnit XML with a test suite per category
+    Junit,
+    /// Checkstyle XML with the findings grouped per file
+    Checkstyle,
+}
+
+impl Format {
+    /// Whether the findings are written to stdout instead of being logged
+    pub fn is_machine_readable(&self) -> bool {
+        *self != Format::Text
+    }
+}
+
+/// Renders the report in the given format.
+///
+/// The text format is logged directly so nothing is returned for it.
+pub fn render(
+    report: &AnalysisReport,
+    options: &Options,
+    format: Format,
+) -> anyhow::Result<Option<String>> {
+    match format {
+        Format::Text => {
+            report.log();
+            Ok(None)
+        }
+        Format::Json => Ok(Some(json::render(report, options)?)),
+        Format::Sarif => Ok(Some(sarif::render(report)?)),
+        Format::Junit => Ok(Some(junit::render(report))),
+        Format::Checkstyle => Ok(Some(checkstyle::render(report))),
+    }
+}
+
+/// Escapes the characters that aren't allowed in XML text and attribute values
+fn xml_escape(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_xml_escape() {
+        assert_eq!(xml_escape("lib/a.dart"), "lib/a.dart");
+        assert_eq!(
+            xml_escape("<a href=\"x\">'&'</a>"),
+            "&lt;a href=&quot;x&quot;&gt;&apos;&amp;&apos;&lt;/a&gt;"
+        );
+    }
+}
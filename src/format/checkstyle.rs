@@ -0,0 +1,80 @@
+//! Checkstyle XML output, with the findings grouped per file.
+
+use std::{collections::BTreeMap, fmt::Write, path::PathBuf};
+
+use super::xml_escape;
+use crate::report::{AnalysisReport, Finding};
+
+/// Renders the report as Checkstyle XML with the findings grouped per file
+pub fn render(report: &AnalysisReport) -> String {
+    let mut files: BTreeMap<&PathBuf, Vec<&Finding>> = BTreeMap::new();
+    for finding in report.findings.iter() {
+        files.entry(&finding.path).or_default().push(finding);
+    }
+    let mut output =
+        String::from("<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<checkstyle version=\"4.3\">\n");
+    for (path, findings) in files {
+        let _ = writeln!(
+            output,
+            r#"  <file name="{}">"#,
+            xml_escape(&path.display().to_string())
+        );
+        for finding in findings {
+            let _ = writeln!(
+                output,
+                r#"    <error line="{}" severity="{}" message="{}: {} ({})" source="{}.{}"/>"#,
+                finding.line.unwrap_or(1),
+                finding.severity.as_str(),
+                xml_escape(finding.category.title()),
+                xml_escape(&finding.subject()),
+                xml_escape(&finding.evidence),
+                env!("CARGO_PKG_NAME"),
+                finding.category.rule_id()
+            );
+        }
+        let _ = writeln!(output, "  </file>");
+    }
+    output.push_str("</checkstyle>");
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::report::Category;
+
+    #[test]
+    fn test_render() {
+        let report = AnalysisReport {
+            findings: vec![
+                Finding::new(
+                    Category::UnusedDependency,
+                    PathBuf::from("pubspec.yaml"),
+                    "unused",
+                )
+                .with_name("http")
+                .with_line(Some(4)),
+                Finding::new(
+                    Category::UnusedDependency,
+                    PathBuf::from("pubspec.yaml"),
+                    "unused",
+                )
+                .with_name("provider")
+                .with_line(Some(5)),
+                Finding::new(
+                    Category::UnreferencedFile,
+                    PathBuf::from("lib/a.dart"),
+                    "unused",
+                ),
+            ],
+            ..Default::default()
+        };
+        let output = render(&report);
+        assert_eq!(output.matches("<file ").count(), 2);
+        assert!(output.contains(r#"<file name="pubspec.yaml">"#));
+        assert!(output.contains(
+            r#"<error line="5" severity="error" message="Unused dependency: provider (unused)" source="dart-unused.unused-dependency"/>"#
+        ));
+        assert!(output.contains(r#"<file name="lib/a.dart">"#));
+    }
+}
@@ -0,0 +1,107 @@
+//! JSON output, a single document with the findings, the options and summary counts.
+
+use std::collections::BTreeMap;
+
+use serde::Serialize;
+
+use crate::{
+    cli::Options,
+    report::{AnalysisReport, Category, Finding, Severity},
+};
+
+#[derive(Serialize)]
+struct JsonReport<'a> {
+    tool: &'static str,
+    version: &'static str,
+    project: &'a str,
+    options: &'a Options,
+    entries: &'a [std::path::PathBuf],
+    tests: &'a [std::path::PathBuf],
+    summary: JsonSummary,
+    findings: &'a [Finding],
+}
+
+#[derive(Serialize)]
+struct JsonSummary {
+    reachable_files: usize,
+    total: usize,
+    errors: usize,
+    warnings: usize,
+    info: usize,
+    categories: BTreeMap<Category, usize>,
+}
+
+/// Renders the report as a single JSON document
+pub fn render(report: &AnalysisReport, options: &Options) -> anyhow::Result<String> {
+    let categories = Category::ALL
+        .iter()
+        .map(|category| (*category, report.count(*category)))
+        .collect();
+    let document = JsonReport {
+        tool: env!("CARGO_PKG_NAME"),
+        version: env!("CARGO_PKG_VERSION"),
+        project: &report.project,
+        options,
+        entries: &report.entries,
+        tests: &report.tests,
+        summary: JsonSummary {
+            reachable_files: report.reachable_files,
+            total: report.findings.len(),
+            errors: report
+                .findings
+                .iter()
+                .filter(|x| x.severity == Severity::Error)
+                .count(),
+            warnings: report
+                .findings
+                .iter()
+                .filter(|x| x.severity == Severity::Warning)
+                .count(),
+            info: report
+                .findings
+                .iter()
+                .filter(|x| x.severity == Severity::Info)
+                .count(),
+            categories,
+        },
+        findings: &report.findings,
+    };
+    Ok(serde_json::to_string_pretty(&document)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_json() {
+        let report = AnalysisReport {
+            project: "app".to_string(),
+            findings: vec![
+                Finding::new(
+                    Category::UnusedDependency,
+                    PathBuf::from("pubspec.yaml"),
+                    "unused",
+                )
+                .with_name("http"),
+            ],
+            ..Default::default()
+        };
+        let options = Options {
+            deps: true,
+            ..Default::default()
+        };
+        let output = render(&report, &options).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        assert_eq!(value["project"], "app");
+        assert_eq!(value["options"]["deps"], true);
+        assert_eq!(value["summary"]["total"], 1);
+        assert_eq!(value["summary"]["errors"], 1);
+        assert_eq!(value["summary"]["categories"]["unused_dependency"], 1);
+        assert_eq!(value["summary"]["categories"]["unreferenced_file"], 0);
+        assert_eq!(value["findings"][0]["category"], "unused_dependency");
+        assert_eq!(value["findings"][0]["name"], "http");
+    }
+}
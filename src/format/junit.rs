@@ -0,0 +1,123 @@
+//! JUnit XML output, with a testsuite per category and a failing testcase per finding.
+
+use std::fmt::Write;
+
+use super::xml_escape;
+use crate::report::{AnalysisReport, Severity};
+
+/// Renders the report as JUnit XML.
+///
+/// Each checked category is a test suite with a test case per finding, which fails unless the
+/// finding is only a warning. Categories without findings have a single passing test case.
+pub fn render(report: &AnalysisReport) -> String {
+    let mut suites = String::new();
+    let mut total_tests = 0;
+    let mut total_failures = 0;
+    for category in report.checked.iter() {
+        let findings: Vec<_> = report.findings_in(*category).collect();
+        let tests = findings.len().max(1);
+        let failures = findings
+            .iter()
+            .filter(|x| x.severity == Severity::Error)
+            .count();
+        total_tests += tests;
+        total_failures += failures;
+        let _ = writeln!(
+            suites,
+            r#"  <testsuite name="{}" tests="{}" failures="{}" errors="0" skipped="0">"#,
+            category.rule_id(),
+            tests,
+            failures
+        );
+        if findings.is_empty() {
+            let _ = writeln!(
+                suites,
+                r#"    <testcase name="No {}" classname="{}"/>"#,
+                xml_escape(&category.title().to_lowercase()),
+                category.rule_id()
+            );
+        }
+        for finding in findings {
+            let line = finding
+                .line
+                .map(|line| format!(r#" line="{}""#, line))
+                .unwrap_or_default();
+            let _ = writeln!(
+                suites,
+                r#"    <testcase name="{}" classname="{}" file="{}"{}>"#,
+                xml_escape(&finding.subject()),
+                category.rule_id(),
+                xml_escape(&finding.path.display().to_string()),
+                line
+            );
+            if finding.severity == Severity::Error {
+                let _ = writeln!(
+                    suites,
+                    r#"      <failure type="{}" message="{}: {}">{}</failure>"#,
+                    category.rule_id(),
+                    xml_escape(category.title()),
+                    xml_escape(&finding.subject()),
+                    xml_escape(&finding.evidence)
+                );
+            } else {
+                let _ = writeln!(
+                    suites,
+                    "      <system-out>{}: {}: {} ({})</system-out>",
+                    finding.severity.as_str(),
+                    xml_escape(category.title()),
+                    xml_escape(&finding.subject()),
+                    xml_escape(&finding.evidence)
+                );
+            }
+            let _ = writeln!(suites, "    </testcase>");
+        }
+        let _ = writeln!(suites, "  </testsuite>");
+    }
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<testsuites name=\"{}\" tests=\"{}\" failures=\"{}\">\n{}</testsuites>",
+        env!("CARGO_PKG_NAME"),
+        total_tests,
+        total_failures,
+        suites
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+    use crate::report::{Category, Finding};
+
+    #[test]
+    fn test_render() {
+        let report = AnalysisReport {
+            checked: vec![Category::UnusedDependency, Category::UnreferencedFile],
+            findings: vec![
+                Finding::new(
+                    Category::UnusedDependency,
+                    PathBuf::from("pubspec.yaml"),
+                    "unused",
+                )
+                .with_name("http")
+                .with_line(Some(4)),
+            ],
+            ..Default::default()
+        };
+        let output = render(&report);
+        assert!(output.contains(r#"<testsuites name="dart-unused" tests="2" failures="1">"#));
+        assert!(output.contains(
+            r#"<testsuite name="unused-dependency" tests="1" failures="1" errors="0" skipped="0">"#
+        ));
+        assert!(output.contains(
+            r#"<testcase name="http" classname="unused-dependency" file="pubspec.yaml" line="4">"#
+        ));
+        assert!(output.contains(
+            r#"<failure type="unused-dependency" message="Unused dependency: http">unused</failure>"#
+        ));
+        assert!(output.contains(
+            r#"<testsuite name="unreferenced-file" tests="1" failures="0" errors="0" skipped="0">"#
+        ));
+        assert!(!output.contains("unused-label"));
+    }
+}
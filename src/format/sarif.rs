@@ -0,0 +1,142 @@
+//! SARIF output, with a rule for each category of finding.
+
+use std::path::Path;
+
+use serde_json::{Value, json};
+
+use crate::report::{AnalysisReport, Category, Finding, Severity};
+
+const SCHEMA: &str = "https://json.schemastore.org/sarif-2.1.0.json";
+
+/// Renders the report as a SARIF 2.1.0 log with a rule for each category
+pub fn render(report: &AnalysisReport) -> anyhow::Result<String> {
+    let rules: Vec<Value> = Category::ALL.iter().map(rule).collect();
+    let results: Vec<Value> = report.findings.iter().map(result).collect();
+    let log = json!({
+        "$schema": SCHEMA,
+        "version": "2.1.0",
+        "runs": [{
+            "tool": {
+                "driver": {
+                    "name": env!("CARGO_PKG_NAME"),
+                    "version": env!("CARGO_PKG_VERSION"),
+                    "informationUri": env!("CARGO_PKG_REPOSITORY"),
+                    "rules": rules,
+                }
+            },
+            "results": results,
+        }]
+    });
+    Ok(serde_json::to_string_pretty(&log)?)
+}
+
+fn rule(category: &Category) -> Value {
+    json!({
+        "id": category.rule_id(),
+        "name": category.title(),
+        "shortDescription": { "text": category.title() },
+        "fullDescription": { "text": category.description() },
+        "defaultConfiguration": { "level": "error" },
+    })
+}
+
+fn result(finding: &Finding) -> Value {
+    let rule_index = Category::ALL
+        .iter()
+        .position(|x| *x == finding.category)
+        .unwrap_or_default();
+    let mut location = json!({
+        "artifactLocation": {
+            "uri": uri(&finding.path),
+            "uriBaseId": "%SRCROOT%",
+        }
+    });
+    if let Some(line) = finding.line {
+        location["region"] = json!({ "startLine": line });
+    }
+    json!({
+        "ruleId": finding.category.rule_id(),
+        "ruleIndex": rule_index,
+        "level": level(finding.severity),
+        "message": {
+            "text": format!("{}: {} ({})", finding.category.title(), finding.subject(), finding.evidence),
+        },
+        "locations": [{ "physicalLocation": location }],
+    })
+}
+
+/// Maps the severity to a SARIF result level
+fn level(severity: Severity) -> &'static str {
+    match severity {
+        Severity::Error => "error",
+        Severity::Warning => "warning",
+        Severity::Info | Severity::Off => "note",
+    }
+}
+
+/// Converts the relative path into a URI reference, escaping the characters that aren't allowed
+fn uri(path: &Path) -> String {
+    let path = path.to_string_lossy().replace('\\', "/");
+    let mut uri = String::with_capacity(path.len());
+    for c in path.chars() {
+        match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '-' | '.' | '_' | '~' | '/' => uri.push(c),
+            _ => {
+                let mut buf = [0; 4];
+                for byte in c.encode_utf8(&mut buf).bytes() {
+                    uri.push_str(&format!("%{:02X}", byte));
+                }
+            }
+        }
+    }
+    uri
+}
+
+#[cfg(test)]
+mod tests {
+    use std::path::PathBuf;
+
+    use super::*;
+
+    #[test]
+    fn test_uri() {
+        assert_eq!(uri(Path::new("lib/main.dart")), "lib/main.dart");
+        assert_eq!(uri(Path::new("lib/my file.dart")), "lib/my%20file.dart");
+        assert_eq!(uri(Path::new("lib/é.dart")), "lib/%C3%A9.dart");
+    }
+
+    #[test]
+    fn test_render() {
+        let report = AnalysisReport {
+            findings: vec![
+                Finding::new(
+                    Category::UnusedDependency,
+                    PathBuf::from("pubspec.yaml"),
+                    "unused",
+                )
+                .with_name("http")
+                .with_line(Some(12)),
+                Finding::new(
+                    Category::UnreferencedFile,
+                    PathBuf::from("lib/a.dart"),
+                    "unused",
+                ),
+            ],
+            ..Default::default()
+        };
+        let output = render(&report).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&output).unwrap();
+        let run = &value["runs"][0];
+        assert_eq!(
+            run["tool"]["driver"]["rules"].as_array().unwrap().len(),
+            Category::ALL.len()
+        );
+        let results = run["results"].as_array().unwrap();
+        assert_eq!(results[0]["ruleId"], "unused-dependency");
+        let location = &results[0]["locations"][0]["physicalLocation"];
+        assert_eq!(location["artifactLocation"]["uri"], "pubspec.yaml");
+        assert_eq!(location["region"]["startLine"], 12);
+        assert_eq!(results[1]["ruleId"], "unreferenced-file");
+        assert!(results[1]["locations"][0]["physicalLocation"]["region"].is_null());
+    }
+}
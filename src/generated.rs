@@ -0,0 +1,85 @@
+//! Finds the files written by code generators and the source files they are generated from.
+
+use std::path::{Path, PathBuf};
+
+use glob::glob;
+
+/// Suffixes of the files written by build_runner and the other common code generators.
+///
+/// `.gen.dart` is left out as flutter_gen generates `assets.gen.dart` from the pubspec rather
+/// than from `assets.dart`.
+pub const GENERATED_SUFFIXES: [&str; 5] = [
+    ".g.dart",
+    ".freezed.dart",
+    ".gr.dart",
+    ".config.dart",
+    ".mocks.dart",
+];
+
+/// Directories generated files are looked for in
+const DIRS: [&str; 3] = ["lib", "test", "integration_test"];
+
+/// Finds the generated files of the project, the files ending with one of the suffixes
+pub fn find(suffixes: &[String]) -> anyhow::Result<Vec<PathBuf>> {
+    let mut files = Vec::new();
+    for dir in DIRS {
+        for suffix in suffixes {
+            files.extend(glob(&format!("{}/**/*{}", dir, suffix))?.flatten());
+        }
+    }
+    files.sort();
+    files.dedup();
+    Ok(files)
+}
+
+/// Gets the source file a generated file is generated from, e.g. `a.dart` for `a.g.dart`.
+///
+/// The longest matching suffix is used, so with both `.g.dart` and `.config.g.dart` set
+/// `a.config.g.dart` is paired with `a.dart`.
+pub fn source_of(file: &Path, suffixes: &[String]) -> Option<PathBuf> {
+    let name = file.file_name()?.to_str()?;
+    let stem = suffixes
+        .iter()
+        .filter_map(|suffix| name.strip_suffix(suffix.as_str()))
+        .filter(|stem| !stem.is_empty())
+        .min_by_key(|stem| stem.len())?;
+    Some(file.with_file_name(format!("{}.dart", stem)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn suffixes() -> Vec<String> {
+        [".g.dart", ".freezed.dart", ".config.dart", ".mocks.dart"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_source_of() {
+        assert_eq!(
+            source_of(Path::new("lib/src/user.freezed.dart"), &suffixes()),
+            Some(PathBuf::from("lib/src/user.dart"))
+        );
+        assert_eq!(
+            source_of(Path::new("test/api_test.mocks.dart"), &suffixes()),
+            Some(PathBuf::from("test/api_test.dart"))
+        );
+        let mut nested = suffixes();
+        nested.push(".config.g.dart".to_string());
+        assert_eq!(
+            source_of(Path::new("lib/injection.config.g.dart"), &nested),
+            Some(PathBuf::from("lib/injection.dart"))
+        );
+        assert_eq!(source_of(Path::new("lib/user.dart"), &suffixes()), None);
+        // flutter_gen files aren't generated from a source file
+        let defaults: Vec<String> = GENERATED_SUFFIXES.iter().map(|x| x.to_string()).collect();
+        assert_eq!(
+            source_of(Path::new("lib/gen/assets.gen.dart"), &defaults),
+            None
+        );
+        assert_eq!(source_of(Path::new("lib/.g.dart"), &suffixes()), None);
+    }
+}
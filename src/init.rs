@@ -0,0 +1,355 @@
+//! The `init` command, which inspects the project and writes a starter config.
+
+use std::{
+    fmt::Write,
+    path::{Path, PathBuf},
+};
+
+use glob::glob;
+use log::info;
+
+use crate::{
+    config::CONFIG_FILE,
+    entry::{self, DEFAULT_ENTRY, FLAVOUR_ENTRIES},
+    generated::GENERATED_SUFFIXES,
+    locator,
+    pubspec::PubspecSchema,
+};
+
+/// Dependencies that are used by tooling or the platform rather than imported
+const TOOLING_DEPS: [&str; 5] = [
+    "cupertino_icons",
+    "intl_utils",
+    "flutter_launcher_icons",
+    "flutter_native_splash",
+    "build_runner",
+];
+
+/// What was found in the project to base the starter config on
+#[derive(Debug, Default)]
+pub struct Detected {
+    /// Whether the project is analysed as a package
+    pub package: bool,
+    /// The `lib/main*.dart` entry points
+    pub entries: Vec<PathBuf>,
+    /// Patterns of the generated files found with the number of files matching
+    pub generated: Vec<(String, usize)>,
+    /// Localisation setup, e.g. flutter_intl or l10n.yaml
+    pub localisation: Option<String>,
+    /// Directory of the ARB files of the localisation setup
+    pub arb_dir: Option<String>,
+    /// Class the localisation setup generates
+    pub class_name: Option<String>,
+    /// Name of the variable holding the GetIt instance
+    pub locator: Option<String>,
+    /// Whether get_it is a dependency
+    pub uses_get_it: bool,
+    /// Asset directories and files registered in the pubspec
+    pub assets: Vec<PathBuf>,
+    /// Dependencies used by tooling rather than imported
+    pub tooling_deps: Vec<String>,
+}
+
+/// Writes a starter config to the project root, this must be called after the current directory
+/// is set to the project root
+pub fn init(pubspec: &PubspecSchema, force: bool) -> anyhow::Result<PathBuf> {
+    let path = PathBuf::from(CONFIG_FILE);
+    if path.exists() && !force {
+        return Err(anyhow::anyhow!(
+            "{} already exists, use --force to overwrite it",
+            CONFIG_FILE
+        ));
+    }
+    let contents = render(&detect(pubspec)?);
+    std::fs::write(&path, contents)
+        .map_err(|e| anyhow::anyhow!("Failed to write {:?}: {}", path, e))?;
+    info!("Config written to {:?}", path);
+    Ok(path)
+}
+
+/// Inspects the project for the settings of the config
+pub fn detect(pubspec: &PubspecSchema) -> anyhow::Result<Detected> {
+    let mut detected = Detected {
+        package: entry::is_package(Path::new(""), pubspec),
+        uses_get_it: pubspec.dependencies.contains_key("get_it"),
+        assets: pubspec.flutter.get_asset_paths(),
+        ..Default::default()
+    };
+    if Path::new(DEFAULT_ENTRY).is_file() {
+        detected.entries.push(PathBuf::from(DEFAULT_ENTRY));
+    }
+    let mut flavours: Vec<PathBuf> = glob(FLAVOUR_ENTRIES)?.flatten().collect();
+    flavours.sort();
+    detected.entries.extend(flavours);
+
+    for suffix in GENERATED_SUFFIXES {
+        let pattern = format!("lib/**/*{}", suffix);
+        let count = glob(&pattern)?.flatten().count();
+        if count > 0 {
+            detected.generated.push((pattern, count));
+        }
+    }
+
+    if pubspec.flutter_intl.enabled {
+        detected.localisation = Some(format!(
+            "flutter_intl is enabled in pubspec.yaml with the class {}",
+            pubspec.flutter_intl.class_name
+        ));
+        detected.arb_dir = Some(pubspec.flutter_intl.arb_dir.display().to_string());
+        detected.class_name = Some(pubspec.flutter_intl.class_name.clone());
+    } else if let Ok(contents) = std::fs::read_to_string("l10n.yaml") {
+        let arb_dir = yaml_value(&contents, "arb-dir").unwrap_or("lib/l10n");
+        let class = yaml_value(&contents, "output-class").unwrap_or("AppLocalizations");
+        detected.localisation = Some(format!(
+            "l10n.yaml generates the class {} from the ARB files in {}",
+            class, arb_dir
+        ));
+        detected.arb_dir = Some(arb_dir.to_string());
+        detected.class_name = Some(class.to_string());
+    }
+
+    if detected.uses_get_it {
+        for path in glob("lib/**/*.dart")?.flatten() {
+            if let Ok(contents) = std::fs::read_to_string(&path)
+                && let Some(name) = locator::find_name(&contents)
+            {
+                detected.locator = Some(name);
+                break;
+            }
+        }
+    }
+
+    let mut tooling_deps: Vec<String> = TOOLING_DEPS
+        .iter()
+        .filter(|x| pubspec.dependencies.contains_key(**x))
+        .map(|x| x.to_string())
+        .collect();
+    tooling_deps.sort();
+    detected.tooling_deps = tooling_deps;
+    Ok(detected)
+}
+
+/// Gets the value of a top level key of a simple YAML file
+fn yaml_value<'a>(contents: &'a str, key: &str) -> Option<&'a str> {
+    contents.lines().find_map(|line| {
+        let value = line.strip_prefix(key)?.trim_start().strip_prefix(':')?;
+        Some(value.trim().trim_matches(|c| c == '"' || c == '\''))
+    })
+}
+
+/// Writes the starter config with a comment explaining each choice
+pub fn render(detected: &Detected) -> String {
+    let mut out = String::new();
+    // Writing to a string can't fail
+    let _ = write_config(&mut out, detected);
+    out
+}
+
+fn write_config(out: &mut String, detected: &Detected) -> std::fmt::Result {
+    writeln!(
+        out,
+        "# Config of dart-unused, generated by `dart-unused init`."
+    )?;
+    writeln!(
+        out,
+        "# Run `dart-unused config --print` to see the effective config."
+    )?;
+    writeln!(out)?;
+
+    // The entry points found are listed even for a package, e.g. a plugin with flavours
+    if detected.package && detected.entries.is_empty() {
+        writeln!(
+            out,
+            "# No {} was found, so the project is analysed as a package and every library in",
+            DEFAULT_ENTRY
+        )?;
+        writeln!(
+            out,
+            "# lib/ outside of lib/src/ is an entry point. List the entry points to analyse it as an app."
+        )?;
+        writeln!(out, "entries: []")?;
+    } else if detected.entries.is_empty() {
+        writeln!(
+            out,
+            "# No {} or {} entry points were found, list the entry points of the app.",
+            DEFAULT_ENTRY, FLAVOUR_ENTRIES
+        )?;
+        writeln!(out, "entries: []")?;
+    } else if detected.entries.len() > 1 || detected.entries[0] != Path::new(DEFAULT_ENTRY) {
+        writeln!(
+            out,
+            "# Flavour entry points were found, a file is live when any of them reaches it."
+        )?;
+        writeln!(out, "entries:")?;
+        for entry in detected.entries.iter() {
+            writeln!(out, "  - \"{}\"", entry.display())?;
+        }
+    } else {
+        writeln!(
+            out,
+            "# Only {} was found, which is the default entry point.",
+            DEFAULT_ENTRY
+        )?;
+        writeln!(out, "entries: []")?;
+    }
+    writeln!(out)?;
+
+    writeln!(out, "files:")?;
+    if detected.generated.is_empty() {
+        writeln!(out, "  # No generated files were found.")?;
+        writeln!(out, "  ignore: []")?;
+    } else {
+        writeln!(
+            out,
+            "  # Generated files aren't reported as unreferenced, only when their source is gone."
+        )?;
+        writeln!(out, "  ignore:")?;
+        for (pattern, count) in detected.generated.iter() {
+            writeln!(out, "    - \"{}\" # {} file(s)", pattern, count)?;
+        }
+    }
+    writeln!(
+        out,
+        "  # Files that are always live, e.g. loaded reflectively or marked with @pragma('vm:entry-point')."
+    )?;
+    writeln!(out, "  roots: []")?;
+    writeln!(out)?;
+
+    writeln!(out, "assets:")?;
+    if detected.assets.is_empty() {
+        writeln!(out, "  # No assets are registered in pubspec.yaml.")?;
+    } else {
+        writeln!(
+            out,
+            "  # Checked with --assets. Registered in pubspec.yaml: {}",
+            detected
+                .assets
+                .iter()
+                .map(|x| x.display().to_string())
+                .collect::<Vec<_>>()
+                .join(", ")
+        )?;
+        writeln!(
+            out,
+            "  # Add patterns of the assets that are only loaded by name, e.g. \"assets/flags/*.png\"."
+        )?;
+    }
+    writeln!(out, "  ignore: []")?;
+    writeln!(out)?;
+
+    writeln!(out, "deps:")?;
+    if detected.tooling_deps.is_empty() {
+        writeln!(out, "  # Checked with --deps.")?;
+        writeln!(out, "  ignore: []")?;
+    } else {
+        writeln!(
+            out,
+            "  # Checked with --deps. These dependencies are used by tooling or the platform rather than imported."
+        )?;
+        writeln!(out, "  ignore:")?;
+        for dep in detected.tooling_deps.iter() {
+            writeln!(out, "    - {}", dep)?;
+        }
+    }
+    writeln!(out)?;
+
+    writeln!(out, "labels:")?;
+    match (&detected.localisation, &detected.arb_dir) {
+        (Some(localisation), Some(arb_dir)) => {
+            writeln!(
+                out,
+                "  # {}, the keys of the ARB files in arb_dir are checked with --labels.",
+                localisation
+            )?;
+            writeln!(out, "  arb_dir: \"{}\"", arb_dir)?;
+        }
+        (Some(localisation), None) => writeln!(out, "  # {}.", localisation)?,
+        (None, _) => writeln!(out, "  # No localisation setup was found.")?,
+    }
+    if let Some(class_name) = &detected.class_name {
+        writeln!(out, "  class_name: {}", class_name)?;
+    }
+    writeln!(out, "  severity: error")?;
+    writeln!(out)?;
+
+    writeln!(out, "locators:")?;
+    match &detected.locator {
+        Some(name) => {
+            writeln!(
+                out,
+                "  # The GetIt instance is assigned to `{}`, its registrations are checked with --loc.",
+                name
+            )?;
+            writeln!(out, "  name: {}", name)?;
+        }
+        None if detected.uses_get_it => {
+            writeln!(
+                out,
+                "  # get_it is a dependency but the variable holding the instance wasn't found."
+            )?;
+            writeln!(out, "  name: {}", locator::DEFAULT_NAME)?;
+        }
+        None => writeln!(out, "  # get_it isn't a dependency.")?,
+    }
+    writeln!(out, "  severity: error")?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::parse;
+
+    #[test]
+    fn test_render() {
+        let detected = Detected {
+            entries: vec![
+                PathBuf::from("lib/main_dev.dart"),
+                PathBuf::from("lib/main_prod.dart"),
+            ],
+            generated: vec![("lib/**/*.g.dart".to_string(), 3)],
+            localisation: Some(
+                "l10n.yaml generates the class S from the ARB files in lib/src/l10n".into(),
+            ),
+            arb_dir: Some("lib/src/l10n".to_string()),
+            class_name: Some("S".to_string()),
+            locator: Some("getIt".to_string()),
+            uses_get_it: true,
+            assets: vec![PathBuf::from("assets/images/")],
+            tooling_deps: vec!["cupertino_icons".to_string()],
+            ..Default::default()
+        };
+        let config = parse(&render(&detected)).unwrap();
+        assert_eq!(
+            config.entries,
+            vec!["lib/main_dev.dart", "lib/main_prod.dart"]
+        );
+        let package = Detected {
+            package: true,
+            entries: detected.entries.clone(),
+            ..Default::default()
+        };
+        assert_eq!(parse(&render(&package)).unwrap().entries, config.entries);
+        assert_eq!(config.files.ignore, vec!["lib/**/*.g.dart"]);
+        assert_eq!(config.deps.ignore, vec!["cupertino_icons"]);
+        assert_eq!(config.locators.name.as_deref(), Some("getIt"));
+        assert_eq!(config.labels.arb_dir.as_deref(), Some("lib/src/l10n"));
+        assert_eq!(config.labels.class_name.as_deref(), Some("S"));
+    }
+
+    #[test]
+    fn test_render_empty() {
+        let config = parse(&render(&Detected::default())).unwrap();
+        assert!(config.entries.is_empty());
+        assert!(config.files.ignore.is_empty());
+        assert_eq!(config.locators.name, None);
+    }
+
+    #[test]
+    fn test_yaml_value() {
+        let contents = "arb-dir: lib/src/l10n\noutput-class: 'L10n'\n";
+        assert_eq!(yaml_value(contents, "arb-dir"), Some("lib/src/l10n"));
+        assert_eq!(yaml_value(contents, "output-class"), Some("L10n"));
+        assert_eq!(yaml_value(contents, "template-arb-file"), None);
+    }
+}
@@ -0,0 +1,341 @@
+//! Splits Dart source into tokens so the checks only look at real code.
+//!
+//! The lexer understands line, block and doc comments (block comments nest in Dart), single,
+//! double and triple quoted strings, raw strings and `${...}` interpolation. It doesn't validate
+//! the source, unterminated strings and comments end at the end of the line or file.
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TokenKind {
+    /// Identifier, keyword or number
+    Word,
+    /// String literal including its quotes and interpolations
+    String,
+    /// Any other character of code
+    Symbol,
+    /// `//` comment
+    LineComment,
+    /// `/* */` comment
+    BlockComment,
+    /// `///` or `/** */` comment
+    DocComment,
+}
+
+impl TokenKind {
+    pub fn is_comment(&self) -> bool {
+        matches!(
+            self,
+            TokenKind::LineComment | TokenKind::BlockComment | TokenKind::DocComment
+        )
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Token<'a> {
+    pub kind: TokenKind,
+    pub text: &'a str,
+    /// Byte offset of the token in the source
+    pub offset: usize,
+    /// Line the token starts on, starting at 1
+    pub line: usize,
+}
+
+/// Byte order mark Dart allows at the start of a file
+pub const BOM: char = '\u{feff}';
+
+/// Splits the source into tokens, the whitespace between them is left out
+pub fn tokenize(source: &str) -> Vec<Token<'_>> {
+    let mut lexer = Lexer {
+        source,
+        bytes: source.as_bytes(),
+        pos: if source.starts_with(BOM) {
+            BOM.len_utf8()
+        } else {
+            0
+        },
+        line: 1,
+        tokens: Vec::new(),
+    };
+    lexer.code(false);
+    lexer.tokens
+}
+
+/// Gets the source with the comments blanked out, doc comments are kept when `doc_comments` is set.
+///
+/// The comments are replaced with spaces so the offsets and lines stay the same as the source.
+pub fn code(source: &str, tokens: &[Token], doc_comments: bool) -> String {
+    let mut code = String::with_capacity(source.len());
+    let mut pos = 0;
+    for token in tokens
+        .iter()
+        .filter(|x| x.kind.is_comment() && !(doc_comments && x.kind == TokenKind::DocComment))
+    {
+        code.push_str(&source[pos..token.offset]);
+        for c in token.text.chars() {
+            if c == '\n' || c == '\r' {
+                code.push(c);
+            } else {
+                // one space per byte keeps the offsets of multi-byte characters
+                code.extend(std::iter::repeat_n(' ', c.len_utf8()));
+            }
+        }
+        pos = token.offset + token.text.len();
+    }
+    code.push_str(&source[pos..]);
+    code
+}
+
+struct Lexer<'a> {
+    source: &'a str,
+    bytes: &'a [u8],
+    pos: usize,
+    line: usize,
+    tokens: Vec<Token<'a>>,
+}
+
+impl<'a> Lexer<'a> {
+    fn peek(&self, offset: usize) -> Option<u8> {
+        self.bytes.get(self.pos + offset).copied()
+    }
+
+    fn push(&mut self, kind: TokenKind, start: usize, line: usize) {
+        self.tokens.push(Token {
+            kind,
+            text: &self.source[start..self.pos],
+            offset: start,
+            line,
+        });
+    }
+
+    /// Moves past the character at the current position, counting the lines
+    fn advance(&mut self) {
+        if self.bytes[self.pos] == b'\n' {
+            self.line += 1;
+        }
+        let len = self.source[self.pos..]
+            .chars()
+            .next()
+            .map_or(1, char::len_utf8);
+        self.pos += len;
+    }
+
+    /// Lexes code until the end of the source or, within an interpolation, its closing brace
+    fn code(&mut self, interpolation: bool) {
+        let mut depth = 0;
+        while let Some(c) = self.peek(0) {
+            let start = self.pos;
+            let line = self.line;
+            match c {
+                b'/' if self.peek(1) == Some(b'/') => self.line_comment(),
+                b'/' if self.peek(1) == Some(b'*') => self.block_comment(),
+                b'\'' | b'"' => self.string(start, false),
+                b'r' if matches!(self.peek(1), Some(b'\'' | b'"')) => {
+                    self.pos += 1;
+                    self.string(start, true)
+                }
+                b'{' => {
+                    depth += 1;
+                    self.pos += 1;
+                    self.push(TokenKind::Symbol, start, line);
+                }
+                b'}' if interpolation && depth == 0 => {
+                    self.pos += 1;
+                    return;
+                }
+                b'}' => {
+                    depth -= 1;
+                    self.pos += 1;
+                    self.push(TokenKind::Symbol, start, line);
+                }
+                c if c.is_ascii_whitespace() => self.advance(),
+                c if is_word(c) => {
+                    while self.peek(0).is_some_and(is_word) {
+                        self.pos += 1;
+                    }
+                    self.push(TokenKind::Word, start, line);
+                }
+                _ => {
+                    self.advance();
+                    self.push(TokenKind::Symbol, start, line);
+                }
+            }
+        }
+    }
+
+    fn line_comment(&mut self) {
+        let start = self.pos;
+        while self.peek(0).is_some_and(|c| c != b'\n') {
+            self.advance();
+        }
+        let text = &self.source[start..self.pos];
+        let kind = if text.starts_with("///") && !text.starts_with("////") {
+            TokenKind::DocComment
+        } else {
+            TokenKind::LineComment
+        };
+        self.push(kind, start, self.line);
+    }
+
+    fn block_comment(&mut self) {
+        let start = self.pos;
+        let line = self.line;
+        self.pos += 2;
+        let mut depth = 1;
+        while depth > 0 && self.peek(0).is_some() {
+            if self.peek(0) == Some(b'/') && self.peek(1) == Some(b'*') {
+                depth += 1;
+                self.pos += 2;
+            } else if self.peek(0) == Some(b'*') && self.peek(1) == Some(b'/') {
+                depth -= 1;
+                self.pos += 2;
+            } else {
+                self.advance();
+            }
+        }
+        let text = &self.source[start..self.pos];
+        let kind = if text.starts_with("/**") && text != "/**/" {
+            TokenKind::DocComment
+        } else {
+            TokenKind::BlockComment
+        };
+        self.push(kind, start, line);
+    }
+
+    /// Lexes a string starting at the quote at the current position, `start` includes the `r` of
+    /// raw strings
+    fn string(&mut self, start: usize, raw: bool) {
+        let line = self.line;
+        let quote = self.bytes[self.pos];
+        let triple = self.peek(1) == Some(quote) && self.peek(2) == Some(quote);
+        self.pos += if triple { 3 } else { 1 };
+        // Interpolations are part of the string token
+        let tokens = self.tokens.len();
+        while let Some(c) = self.peek(0) {
+            if c == quote
+                && (!triple || (self.peek(1) == Some(quote) && self.peek(2) == Some(quote)))
+            {
+                self.pos += if triple { 3 } else { 1 };
+                break;
+            } else if c == b'\n' && !triple {
+                break;
+            } else if c == b'\\' && !raw && self.pos + 1 < self.bytes.len() {
+                self.pos += 1;
+                self.advance();
+            } else if c == b'$' && !raw && self.peek(1) == Some(b'{') {
+                self.pos += 2;
+                self.code(true);
+            } else {
+                self.advance();
+            }
+        }
+        self.tokens.truncate(tokens);
+        self.push(TokenKind::String, start, line);
+    }
+}
+
+fn is_word(c: u8) -> bool {
+    c.is_ascii_alphanumeric() || c == b'_' || c == b'$'
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn kinds(source: &str) -> Vec<(TokenKind, &str)> {
+        tokenize(source)
+            .into_iter()
+            .map(|x| (x.kind, x.text))
+            .collect()
+    }
+
+    #[test]
+    fn test_tokenize() {
+        assert_eq!(
+            kinds("import 'a.dart'; // note"),
+            vec![
+                (TokenKind::Word, "import"),
+                (TokenKind::String, "'a.dart'"),
+                (TokenKind::Symbol, ";"),
+                (TokenKind::LineComment, "// note"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bom() {
+        let tokens = tokenize("\u{feff}import 'a.dart';");
+        assert_eq!(tokens[0].text, "import");
+        assert_eq!(tokens[0].offset, 3);
+        // Only a mark at the start of the file is skipped
+        assert_eq!(kinds("a \u{feff}")[1], (TokenKind::Symbol, "\u{feff}"));
+    }
+
+    #[test]
+    fn test_comments() {
+        assert_eq!(
+            kinds("/// doc\n/* a /* nested */ b */ x /** block doc */"),
+            vec![
+                (TokenKind::DocComment, "/// doc"),
+                (TokenKind::BlockComment, "/* a /* nested */ b */"),
+                (TokenKind::Word, "x"),
+                (TokenKind::DocComment, "/** block doc */"),
+            ]
+        );
+        assert_eq!(kinds("//// not doc")[0].0, TokenKind::LineComment);
+    }
+
+    #[test]
+    fn test_strings() {
+        assert_eq!(
+            kinds(r#"'http://a' "it's" r'\' '\'' x"#),
+            vec![
+                (TokenKind::String, "'http://a'"),
+                (TokenKind::String, "\"it's\""),
+                (TokenKind::String, r"r'\'"),
+                (TokenKind::String, r"'\''"),
+                (TokenKind::Word, "x"),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_triple_quoted() {
+        let source = "var a = '''\nimport 'b.dart';\n''';\nimport 'c.dart';";
+        let tokens = tokenize(source);
+        assert_eq!(tokens[3].kind, TokenKind::String);
+        assert_eq!(tokens[3].text, "'''\nimport 'b.dart';\n'''");
+        assert_eq!(tokens[5].text, "import");
+        assert_eq!(tokens[5].line, 4);
+    }
+
+    #[test]
+    fn test_interpolation() {
+        assert_eq!(
+            kinds("'a ${b('}')} c' d"),
+            vec![
+                (TokenKind::String, "'a ${b('}')} c'"),
+                (TokenKind::Word, "d")
+            ]
+        );
+        assert_eq!(
+            kinds("'${{'a': 1}['a']}' e"),
+            vec![
+                (TokenKind::String, "'${{'a': 1}['a']}'"),
+                (TokenKind::Word, "e")
+            ]
+        );
+    }
+
+    #[test]
+    fn test_code() {
+        let source = "a /* b\nc */ d // e\n/// f\n'// g'";
+        let tokens = tokenize(source);
+        assert_eq!(
+            code(source, &tokens, false),
+            "a     \n     d     \n     \n'// g'"
+        );
+        assert_eq!(
+            code(source, &tokens, true),
+            "a     \n     d     \n/// f\n'// g'"
+        );
+    }
+}
@@ -4,27 +4,59 @@ use std::{
 };
 
 use glob::glob;
-use log::info;
-use path_dedot::ParseDot;
+use log::{debug, info, warn};
 
 pub mod assets;
+pub mod baseline;
 pub mod cli;
 pub mod config;
+pub mod entry;
+pub mod file_id;
+pub mod format;
+pub mod generated;
+pub mod init;
+pub mod lexer;
 pub mod localisation;
 pub mod locator;
 pub mod parser;
 pub mod pubspec;
+pub mod report;
+pub mod suppress;
 pub mod util;
 
 use crate::{
     assets::{OsStringWithStr, get_all_items_in_asset_dir, get_assets},
+    file_id::{DirEntries, FileId},
     localisation::all_localisation,
+    report::{AnalysisReport, Category, Finding, Severity},
 };
 
 struct ExtractData {
     labels_referenced: HashSet<String>,
-    locators: HashMap<String, bool>,
-    referenced_files: HashSet<PathBuf>,
+    locators: HashMap<String, LocatorUsage>,
+    /// The files reachable from the entry points
+    referenced_files: HashSet<FileId>,
+    /// The files reachable from the test files
+    test_referenced_files: HashSet<FileId>,
+    /// Local files referenced by each file that has been read
+    references: HashMap<FileId, References>,
+    /// References to files that don't exist
+    broken_references: Vec<BrokenReference>,
+    /// References whose path differs in case from the file
+    case_mismatches: Vec<CaseMismatch>,
+    /// The `part` directives of the files that have been read
+    part_claims: Vec<PartClaim>,
+    /// Names of the libraries with a `library` directive
+    library_names: HashMap<FileId, String>,
+    /// Listings of the directories the references resolve to
+    dir_entries: DirEntries,
+    /// The conditions conditional imports and exports are resolved for, every URI is followed
+    /// when empty
+    conditions: Vec<String>,
+    /// Name of the class the localisations are retrieved from
+    class_name: String,
+    /// Name of the variable holding the GetIt instance
+    locator_name: String,
 }
 
 impl ExtractData {
@@ -33,21 +65,152 @@ impl ExtractData {
             labels_referenced: HashSet::with_capacity(10_000),
             locators: HashMap::with_capacity(300),
             referenced_files: HashSet::with_capacity(10_000),
+            test_referenced_files: HashSet::with_capacity(10_000),
+            references: HashMap::with_capacity(10_000),
+            broken_references: Vec::new(),
+            case_mismatches: Vec::new(),
+            part_claims: Vec::new(),
+            library_names: HashMap::new(),
+            dir_entries: DirEntries::default(),
+            conditions: Vec::new(),
+            class_name: String::new(),
+            locator_name: locator::DEFAULT_NAME.to_string(),
+        }
+    }
+
+    /// Gets the walk that reaches a file, the app when both do and `None` when neither does
+    fn reached_by(&self, file: &FileId) -> Option<Root> {
+        if self.referenced_files.contains(file) {
+            Some(Root::App)
+        } else if self.test_referenced_files.contains(file) {
+            Some(Root::Test)
+        } else {
+            None
         }
     }
 }
 
-pub fn get_unreferenced_files(args: cli::Options) -> anyhow::Result<()> {
-    let config: config::Config = if let Ok(s) = std::fs::read_to_string("unused.config.yaml") {
-        serde_yaml2::from_str(&s).unwrap()
-    } else {
-        Default::default()
-    };
+/// An import, export or part of a file that doesn't exist
+#[derive(Debug)]
+struct BrokenReference {
+    /// File with the directive
+    source: FileId,
+    line: usize,
+    /// The directive keyword and its URI, e.g. `import 'a.dart'`
+    directive: String,
+    uri: String,
+    target: FileId,
+}
 
-    info!("Analyzing project at {:?}", args.path);
-    util::set_current_dir(&args.path)?;
+/// A library including a part with a `part` directive
+#[derive(Debug)]
+struct PartClaim {
+    library: FileId,
+    part: FileId,
+}
+
+/// An import, export or part whose path differs in case from the file it refers to
+#[derive(Debug)]
+struct CaseMismatch {
+    /// File with the directive
+    source: FileId,
+    line: usize,
+    keyword: &'static str,
+    uri: String,
+    /// The URI with the casing of the file
+    fixed: String,
+    /// The file with its casing on disk
+    target: FileId,
+}
+
+/// Where a class is registered in the locator and whether it is retrieved
+#[derive(Debug, Default)]
+struct LocatorUsage {
+    /// File the class is first registered in
+    registered_in: Option<FileId>,
+    used: bool,
+}
+
+/// The kind of entry point a walk starts from
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Root {
+    /// Entry point of the app, the contents of the files reached are checked
+    App,
+    /// Test file, only the files reached are recorded
+    Test,
+}
+
+/// Local files referenced by a Dart file
+#[derive(Debug, Default, Clone)]
+struct References {
+    /// Files referenced with `import` or `export`, these are walked in turn
+    files: Vec<FileId>,
+    /// Files referenced with `part`, these are walked as well so their code is checked
+    parts: Vec<FileId>,
+}
+
+/// Runs `f` with the current directory set to the project root, changing back to the previous
+/// current directory afterwards whether `f` succeeds or not
+fn in_project<T>(path: &PathBuf, f: impl FnOnce() -> anyhow::Result<T>) -> anyhow::Result<T> {
+    /// Changes back to the directory when dropped, so it is also done when `f` panics
+    struct Restore(PathBuf);
+    impl Drop for Restore {
+        fn drop(&mut self) {
+            if let Err(e) = std::env::set_current_dir(&self.0) {
+                warn!("Failed to change back to {:?}: {}", self.0, e);
+            }
+        }
+    }
+    let _restore = Restore(std::env::current_dir()?);
+    util::set_current_dir(path)?;
     info!("Current directory set to {:?}", std::env::current_dir()?);
+    f()
+}
+
+/// Reads the pubspec of the project and the effective config, from the project root
+fn load_project(args: &cli::Options) -> anyhow::Result<(pubspec::PubspecSchema, config::Config)> {
     let pubspec = pubspec::get_package_details()?;
+    let config = config::load(args.config.as_deref(), &pubspec)?;
+    Ok((pubspec, config))
+}
+
+/// Gets the effective config of the project, with the configs it extends merged in.
+///
+/// The current directory is the project root during the call, so this must not run alongside
+/// anything else that uses the current directory.
+pub fn effective_config(args: &cli::Options) -> anyhow::Result<config::Config> {
+    in_project(&args.path, || Ok(load_project(args)?.1))
+}
+
+/// Writes a starter config for the project based on what is found in it and returns its path.
+///
+/// The current directory is the project root during the call, so this must not run alongside
+/// anything else that uses the current directory.
+pub fn init_config(args: &cli::Options, force: bool) -> anyhow::Result<PathBuf> {
+    in_project(&args.path, || {
+        let pubspec = pubspec::get_package_details()?;
+        let path = init::init(&pubspec, force)?;
+        Ok(std::path::absolute(&path).unwrap_or(path))
+    })
+}
+
+/// Analyses the project and returns the unused items found, with paths relative to the project
+/// root.
+///
+/// With the `remove` option the unreferenced files and unregistered assets are also deleted.
+///
+/// The current directory is the project root during the call, so this must not run alongside
+/// anything else that uses the current directory. Relative paths in the options other than
+/// `path` are resolved from the project root.
+pub fn get_unreferenced_files(args: cli::Options) -> anyhow::Result<AnalysisReport> {
+    info!("Analyzing project at {:?}", args.path);
+    let path = args.path.clone();
+    in_project(&path, || analyse(args))
+}
+
+/// Analyses the project, the current directory must be the project root
+fn analyse(args: cli::Options) -> anyhow::Result<AnalysisReport> {
+    let (pubspec, config) = load_project(&args)?;
     let mut assets = if args.assets {
         get_assets(pubspec.flutter.get_assets(), &config.assets.ignore)?
     } else {
@@ -58,203 +221,722 @@ pub fn get_unreferenced_files(args: cli::Options) -> anyhow::Result<()> {
         assets.iter().map(|x| x.borrow_path().clone()).collect();
     info!("{} assets registered", assets.len());
     let mut deps: Vec<String> = if args.deps {
-        pubspec.dependencies.keys().cloned().collect()
+        pubspec
+            .dependencies
+            .keys()
+            .filter(|x| !config.deps.ignore.contains(x))
+            .cloned()
+            .collect()
     } else {
         Vec::new()
     };
     let mut extracted_data = ExtractData::new();
-    // TODO allow to set entry point
-    localisation::set_class_name(&pubspec.flutter_intl.class_name)?;
-    let main = PathBuf::from("lib/main.dart");
-    extracted_data.referenced_files.insert(main.clone());
+    extracted_data.conditions = if args.conditions.is_empty() {
+        config.conditions.clone()
+    } else {
+        args.conditions.clone()
+    };
+    extracted_data.class_name = match &config.labels.class_name {
+        Some(class_name) => class_name.clone(),
+        None => pubspec.flutter_intl.class_name.clone(),
+    };
+    if let Some(name) = &config.locators.name {
+        extracted_data.locator_name = name.clone();
+    }
+    // The current directory is the project root, so the entry points are relative to it
+    let project = Path::new("");
+    let mut entries = if args.entries.is_empty()
+        && config.entries.is_empty()
+        && entry::is_package(project, &pubspec)
+    {
+        info!("Analyzing {} as a package", pubspec.name);
+        entry::package_entries(project)?
+    } else {
+        entry::resolve_entries(project, &args.entries, &config.entries)?
+    };
+    for root in entry::app_roots(project)?
+        .into_iter()
+        .chain(entry::config_roots(project, &config.files.roots)?)
+    {
+        if !entries.contains(&root) {
+            entries.push(root);
+        }
+    }
+    if entries.is_empty() {
+        return Err(anyhow::anyhow!("No entry points found"));
+    }
+    let tests = entry::test_roots(project)?;
+    info!(
+        "{} entry point(s) and {} test file(s) found",
+        entries.len(),
+        tests.len()
+    );
 
-    extract_data(
-        &main,
+    // The app is walked first so the files reachable from it have their contents checked
+    let app_roots: Vec<FileId> = entries.iter().map(FileId::new).collect();
+    let test_roots: Vec<FileId> = tests.iter().map(FileId::new).collect();
+    walk(
+        &app_roots,
+        Root::App,
         &pubspec.name,
         &mut extracted_data,
         &mut deps,
         &mut assets,
         &args,
     )?;
+    walk(
+        &test_roots,
+        Root::Test,
+        &pubspec.name,
+        &mut extracted_data,
+        &mut deps,
+        &mut assets,
+        &args,
+    )?;
+    let referenced_by = referenced_by(&extracted_data.references);
+    let app_roots: HashSet<FileId> = app_roots.into_iter().collect();
+    let test_roots: HashSet<FileId> = test_roots.into_iter().collect();
 
-    let dart = glob("lib/**/*.dart").expect("Failed to read glob pattern");
-    let mut dart: Vec<PathBuf> = dart.flatten().collect();
-    dart.retain(|path| !extracted_data.referenced_files.contains(path));
-    if !assets.is_empty() {
-        let assets: Vec<PathBuf> = assets
-            .into_iter()
-            .map(|x| x.borrow_path().to_owned())
-            .collect();
-        for asset in assets.iter().enumerate() {
-            log::error!(
-                "{}. Unreferenced registered assets: {:?}",
-                asset.0 + 1,
-                asset.1
-            );
+    let mut report = AnalysisReport {
+        project: pubspec.name.clone(),
+        entries,
+        tests,
+        checked: args.checked_categories(),
+        ..Default::default()
+    };
+
+    if args.assets {
+        for asset in assets.iter() {
+            report.findings.push(Finding::new(
+                Category::UnreferencedAsset,
+                asset.borrow_path().to_owned(),
+                "registered in pubspec.yaml but not referenced by any reachable file",
+            ));
         }
-        log::info!("");
         let mut all_assets: Vec<PathBuf> =
             get_all_items_in_asset_dir(&pubspec.flutter.get_asset_paths(), &config.assets.ignore)?;
-
         all_assets.retain(|x| !registered_assets.contains(x));
-
-        if !all_assets.is_empty() {
-            for asset in all_assets.iter().enumerate() {
-                log::error!("{}. Unregistered asset: {:?}", asset.0 + 1, asset.1);
-            }
-            log::info!("");
-        }
-        if args.remove {
-            for asset in all_assets.iter() {
-                std::fs::remove_file(asset)?;
-            }
+        all_assets.sort();
+        for asset in all_assets {
+            report.findings.push(Finding::new(
+                Category::UnregisteredAsset,
+                asset,
+                "in an asset directory but not registered in pubspec.yaml",
+            ));
         }
     }
+
     if args.deps {
-        for dep in deps.iter().enumerate() {
-            log::error!("{}. Unused dependencies: {:?}", dep.0 + 1, dep.1);
+        deps.sort();
+        let contents = std::fs::read_to_string("pubspec.yaml")?;
+        for dep in deps.iter() {
+            let line = pubspec::dependency_line(&contents, dep);
+            if line.is_some_and(|x| suppress::is_line_ignored(&contents, x, &["deps"])) {
+                debug!("Dependency {:?} is ignored in pubspec.yaml", dep);
+                continue;
+            }
+            report.findings.push(
+                Finding::new(
+                    Category::UnusedDependency,
+                    PathBuf::from("pubspec.yaml"),
+                    "not imported or mentioned by any reachable file",
+                )
+                .with_name(dep)
+                .with_line(line),
+            );
         }
-        log::info!("");
     }
 
     if args.labels {
         // read arb files to get all localisation keys
-        let mut all_localisation_keys: HashSet<String> = HashSet::with_capacity(10_000);
-        let arb_files = glob("lib/l10n/*.arb").expect("Failed to read glob pattern");
-        for arb in arb_files.flatten() {
-            let contents = std::fs::read_to_string(&arb).expect("Failed to read arb file");
-            let json: serde_json::Value =
-                serde_json::from_str(&contents).expect("Failed to parse arb file");
+        let mut all_localisation_keys: HashMap<String, PathBuf> = HashMap::with_capacity(10_000);
+        let mut kept_keys: HashSet<String> = HashSet::new();
+        let mut arb_contents: HashMap<PathBuf, String> = HashMap::new();
+        let arb_dir = match &config.labels.arb_dir {
+            Some(arb_dir) => PathBuf::from(arb_dir),
+            None => pubspec.flutter_intl.arb_dir.clone(),
+        };
+        let arb_files: Vec<PathBuf> = glob(&arb_dir.join("*.arb").to_string_lossy())?
+            .flatten()
+            .collect();
+        if arb_files.is_empty() {
+            warn!("No ARB files found in {:?}, set labels.arb_dir", arb_dir);
+        }
+        for arb in arb_files {
+            let contents = std::fs::read_to_string(&arb)
+                .map_err(|e| anyhow::anyhow!("Failed to read {:?}: {}", arb, e))?;
+            let json: serde_json::Value = serde_json::from_str(&contents)
+                .map_err(|e| anyhow::anyhow!("Invalid ARB file {:?}: {}", arb, e))?;
             if let serde_json::Value::Object(map) = json {
-                for (key, _) in map.iter() {
-                    all_localisation_keys.insert(key.to_owned());
+                for (key, value) in map.iter() {
+                    // keys starting with @ hold the metadata of the key after it
+                    if let Some(key) = key.strip_prefix('@') {
+                        if suppress::is_arb_key_kept(value) {
+                            kept_keys.insert(key.to_owned());
+                        }
+                        continue;
+                    }
+                    all_localisation_keys
+                        .entry(key.to_owned())
+                        .or_insert_with(|| arb.clone());
                 }
             }
+            arb_contents.insert(arb, contents);
         }
 
-        all_localisation_keys.retain(|x| !extracted_data.labels_referenced.contains(x));
-
-        for label in all_localisation_keys.iter().enumerate() {
-            log::error!(
-                "{}. Unreferenced localisation key: {:?}",
-                label.0 + 1,
-                label.1
+        all_localisation_keys
+            .retain(|x, _| !extracted_data.labels_referenced.contains(x) && !kept_keys.contains(x));
+        let mut labels: Vec<(String, PathBuf)> = all_localisation_keys.into_iter().collect();
+        labels.sort();
+        for (label, arb) in labels {
+            let key = format!("\"{}\"", label);
+            let line = util::find_line(&arb_contents[&arb], |x| x.trim_start().starts_with(&key));
+            report.findings.push(
+                Finding::new(
+                    Category::UnusedLabel,
+                    arb,
+                    "not referenced by any reachable file",
+                )
+                .with_name(&label)
+                .with_line(line),
             );
         }
-        log::info!("");
     }
 
     if args.loc {
-        extracted_data.locators.retain(|_, v| !*v);
-        for (ind, (k, _)) in extracted_data.locators.iter().enumerate() {
-            log::error!("{}. Unused locator: {:?}", ind + 1, k);
+        let mut locators: Vec<(String, PathBuf)> = extracted_data
+            .locators
+            .drain()
+            .filter(|(_, usage)| !usage.used)
+            .filter_map(|(class, usage)| usage.registered_in.map(|x| (class, x.into_path())))
+            .collect();
+        locators.sort();
+        for (class, path) in locators {
+            let contents = std::fs::read_to_string(&path).unwrap_or_default();
+            let line = registration_line(&contents, &class, args.doc_comments);
+            if line.is_some_and(|x| suppress::is_line_ignored(&contents, x, &["locator", "loc"])) {
+                debug!("Locator {:?} is ignored in {:?}", class, path);
+                continue;
+            }
+            report.findings.push(
+                Finding::new(
+                    Category::UnusedLocator,
+                    path,
+                    "registered in the locator but never retrieved",
+                )
+                .with_name(&class)
+                .with_line(line),
+            );
         }
-        log::info!("");
     }
 
-    for file in dart.iter().enumerate() {
-        log::error!("{} Unreferenced file: {:?}", file.0 + 1, file.1);
+    let ignored_files = util::compile_patterns(&config.ignored_files())?;
+    for claim in extracted_data.part_claims.iter() {
+        let part_of = std::fs::read_to_string(&claim.part)
+            .ok()
+            .and_then(|x| read_part_of(&x));
+        let Some((part_of, line)) = part_of else {
+            report.findings.push(
+                Finding::new(
+                    Category::PartMismatch,
+                    claim.part.path().to_path_buf(),
+                    "included as a part but has no part of directive",
+                )
+                .with_target(claim.library.path().to_path_buf()),
+            );
+            continue;
+        };
+        if !part_belongs(
+            claim,
+            &part_of,
+            &pubspec.name,
+            &mut extracted_data.dir_entries,
+            &extracted_data.library_names,
+        ) {
+            report.findings.push(
+                Finding::new(
+                    Category::PartMismatch,
+                    claim.part.path().to_path_buf(),
+                    &format!("part of {}, not the library including it", part_of),
+                )
+                .with_name(&part_of.to_string())
+                .with_line(Some(line))
+                .with_target(claim.library.path().to_path_buf()),
+            );
+        }
     }
+
+    let dart: Vec<PathBuf> = glob("lib/**/*.dart")
+        .expect("Failed to read glob pattern")
+        .flatten()
+        .collect();
+    let suffixes = config.generated_suffixes();
+    let generated_files = generated::find(&suffixes)?;
+    let mut part_files = dart.clone();
+    part_files.extend(
+        generated_files
+            .iter()
+            .filter(|x| !x.starts_with("lib"))
+            .cloned(),
+    );
+    let orphaned_parts = find_orphaned_parts(&part_files, &pubspec.name, &mut extracted_data);
+
+    // Stale generated files and orphaned parts are reported even when they match `files.ignore`,
+    // as generated files usually do, but they are never removed then
+    let mut checked_generated = HashSet::new();
+    for file in generated_files {
+        let id = FileId::new(&file);
+        if extracted_data.referenced_files.contains(&id)
+            || extracted_data.test_referenced_files.contains(&id)
+        {
+            continue;
+        }
+        let Some((source, evidence)) = stale_generated(&file, &suffixes, &orphaned_parts) else {
+            continue;
+        };
+        if std::fs::read_to_string(&file).is_ok_and(|x| suppress::is_file_ignored(&x)) {
+            debug!("{:?} is ignored", file);
+            continue;
+        }
+        report.findings.push(
+            Finding::new(Category::StaleGenerated, id.path().to_path_buf(), evidence)
+                .with_target(source),
+        );
+        checked_generated.insert(id);
+    }
+
+    for file in dart {
+        let id = FileId::new(&file);
+        let reached_by = extracted_data.reached_by(&id);
+        if reached_by == Some(Root::App) {
+            report.reachable_files += 1;
+        } else if checked_generated.contains(&id) {
+            debug!("{:?} was checked as a generated file", file);
+        } else if std::fs::read_to_string(&file).is_ok_and(|x| suppress::is_file_ignored(&x)) {
+            debug!("{:?} is ignored", file);
+        } else if let Some((part_of, line)) = orphaned_parts.get(&id) {
+            report.findings.push(
+                Finding::new(
+                    Category::OrphanedPart,
+                    id.into_path(),
+                    &format!("part of {} but no library includes it", part_of),
+                )
+                .with_line(Some(*line)),
+            );
+        } else if util::matches_any(&ignored_files, &file) {
+            debug!("{:?} is ignored by the config", file);
+        } else if reached_by == Some(Root::Test) {
+            let tests = reached_from(
+                &id,
+                &test_roots,
+                &extracted_data.test_referenced_files,
+                &referenced_by,
+            );
+            report.findings.push(
+                Finding::new(
+                    Category::TestOnlyFile,
+                    id.into_path(),
+                    "only reachable from tests",
+                )
+                .with_reached_from(tests),
+            );
+        } else {
+            report.findings.push(Finding::new(
+                Category::UnreferencedFile,
+                id.into_path(),
+                "not reachable from any entry point or test",
+            ));
+        }
+    }
+
+    for broken in extracted_data.broken_references.drain(..) {
+        report.findings.push(
+            Finding::new(
+                Category::BrokenReference,
+                broken.source.into_path(),
+                &format!("{} refers to a file that doesn't exist", broken.directive),
+            )
+            .with_name(&broken.uri)
+            .with_line(Some(broken.line))
+            .with_target(broken.target.into_path()),
+        );
+    }
+
+    for mismatch in extracted_data.case_mismatches.iter() {
+        report.findings.push(
+            Finding::new(
+                Category::CaseMismatch,
+                mismatch.source.path().to_path_buf(),
+                &format!(
+                    "{} '{}' should be '{}'",
+                    mismatch.keyword, mismatch.uri, mismatch.fixed
+                ),
+            )
+            .with_name(&mismatch.uri)
+            .with_line(Some(mismatch.line))
+            .with_target(mismatch.target.path().to_path_buf()),
+        );
+    }
+
+    // Findings in reachable files, e.g. unused locators or broken references, say which entry
+    // points or tests reach them
+    let mut reached_by: HashMap<FileId, Vec<PathBuf>> = HashMap::new();
+    for finding in report
+        .findings
+        .iter_mut()
+        .filter(|x| x.reached_from.is_empty())
+    {
+        let id = FileId::new(&finding.path);
+        let (roots, reachable) = match extracted_data.reached_by(&id) {
+            Some(Root::App) => (&app_roots, &extracted_data.referenced_files),
+            Some(Root::Test) => (&test_roots, &extracted_data.test_referenced_files),
+            None => continue,
+        };
+        finding.reached_from = reached_by
+            .entry(id)
+            .or_insert_with_key(|id| reached_from(id, roots, reachable, &referenced_by))
+            .clone();
+    }
+
+    report
+        .findings
+        .sort_by(|a, b| (a.category, &a.path, &a.name).cmp(&(b.category, &b.path, &b.name)));
+    for finding in report.findings.iter_mut() {
+        finding.severity = args.severity(config.severity(finding.category), finding.category);
+    }
+    report.findings.retain(|x| x.severity != Severity::Off);
+    if let Some(path) = &args.write_baseline {
+        baseline::Baseline::from_report(&report).write(path)?;
+    }
+    if let Some(path) = &args.baseline {
+        baseline::Baseline::read(path)?.apply(&mut report);
+    }
+
+    if args.fix_case {
+        // Only the mismatches still reported after the baseline are fixed
+        for mismatch in extracted_data.case_mismatches.iter().filter(|x| {
+            report.findings.iter().any(|finding| {
+                finding.category == Category::CaseMismatch
+                    && finding.path == x.source.path()
+                    && finding.line == Some(x.line)
+                    && finding.name.as_deref() == Some(&x.uri)
+            })
+        }) {
+            fix_directive(
+                &mismatch.source,
+                mismatch.line,
+                &mismatch.uri,
+                &mismatch.fixed,
+            )?;
+        }
+    }
+
     if args.remove {
-        for file in dart.iter() {
-            std::fs::remove_file(file)?;
+        for finding in report.findings.iter().filter(|x| {
+            matches!(
+                x.category,
+                Category::UnregisteredAsset
+                    | Category::UnreferencedFile
+                    | Category::OrphanedPart
+                    | Category::StaleGenerated
+            ) && !util::matches_any(&ignored_files, &x.path)
+        }) {
+            std::fs::remove_file(&finding.path)?;
         }
     }
-    Ok(())
+    Ok(report)
 }
 
-fn extract_data(
-    file_path: &std::path::PathBuf,
+/// Walks all the files reachable from the roots at once and records that they are reachable.
+///
+/// Each file is only read once, files already read while walking the app reuse the references
+/// found then. Which of the roots reach a file is only worked out for the files reported, with
+/// `reached_from`.
+fn walk(
+    roots: &[FileId],
+    root: Root,
     package_name: &str,
     extracted_data: &mut ExtractData,
     deps: &mut Vec<String>,
     assets: &mut Vec<OsStringWithStr>,
     args: &cli::Options,
 ) -> anyhow::Result<()> {
-    let contents = std::fs::read_to_string(file_path)
-        .unwrap_or_else(|_| panic!("Failed to read file: {:?}", file_path));
-    for line in contents.lines() {
-        if let Ok((_, dart)) = parser::dart_file(line) {
-            match dart {
-                parser::DartFile::Import(path) => {
-                    // relative path imports
-                    let file = path.replace("%20", " ");
-                    let file = Path::new(&file);
-                    let file = file_path.parent().unwrap().join(file);
-                    if !extracted_data
-                        .referenced_files
-                        .contains(&file.to_path_buf())
+    let mut stack: Vec<FileId> = roots.iter().rev().cloned().collect();
+    match root {
+        Root::App => extracted_data
+            .referenced_files
+            .extend(roots.iter().cloned()),
+        Root::Test => extracted_data
+            .test_referenced_files
+            .extend(roots.iter().cloned()),
+    }
+    while let Some(file) = stack.pop() {
+        if !extracted_data.references.contains_key(&file) {
+            let references = extract_data(
+                &file,
+                root,
+                package_name,
+                extracted_data,
+                deps,
+                assets,
+                args,
+            )?;
+            extracted_data.references.insert(file.clone(), references);
+        }
+        let referenced_files = match root {
+            Root::App => &mut extracted_data.referenced_files,
+            Root::Test => &mut extracted_data.test_referenced_files,
+        };
+        let references = &extracted_data.references[&file];
+        for next in references.files.iter().chain(references.parts.iter()) {
+            if referenced_files.insert(next.clone()) {
+                stack.push(next.clone());
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Gets the files referencing each file that has been read, the references walked backwards
+fn referenced_by(references: &HashMap<FileId, References>) -> HashMap<FileId, Vec<FileId>> {
+    let mut referenced_by: HashMap<FileId, Vec<FileId>> = HashMap::with_capacity(references.len());
+    for (file, references) in references.iter() {
+        for next in references.files.iter().chain(references.parts.iter()) {
+            referenced_by
+                .entry(next.clone())
+                .or_default()
+                .push(file.clone());
+        }
+    }
+    referenced_by
+}
+
+/// Finds the roots reaching the file by following the references back from it.
+///
+/// Only the files in `reachable`, the ones the walk from the roots reached, are followed, as the
+/// references of the other files weren't walked.
+fn reached_from(
+    file: &FileId,
+    roots: &HashSet<FileId>,
+    reachable: &HashSet<FileId>,
+    referenced_by: &HashMap<FileId, Vec<FileId>>,
+) -> Vec<PathBuf> {
+    let mut visited = HashSet::from([file]);
+    let mut stack = vec![file];
+    let mut reached_from = Vec::new();
+    while let Some(file) = stack.pop() {
+        if roots.contains(file) {
+            reached_from.push(file.path().to_path_buf());
+        }
+        for next in referenced_by.get(file).into_iter().flatten() {
+            if reachable.contains(next) && visited.insert(next) {
+                stack.push(next);
+            }
+        }
+    }
+    reached_from.sort();
+    reached_from
+}
+
+/// Finds the line the class is registered in the locator on.
+///
+/// Only the code is searched, so a commented out registration above it isn't found instead.
+fn registration_line(source: &str, class: &str, doc_comments: bool) -> Option<usize> {
+    let class_type = format!("<{}>", class);
+    let code = lexer::code(source, &lexer::tokenize(source), doc_comments);
+    util::find_line(&code, |x| x.contains("register") && x.contains(&class_type))
+}
+
+/// Gets the `part of` directive of a file and its line
+fn read_part_of(source: &str) -> Option<(parser::PartOf, usize)> {
+    parser::directives(source)
+        .into_iter()
+        .find_map(|x| match x.dart {
+            Some(parser::DartFile::PartOf(part_of)) => Some((part_of, x.line)),
+            _ => None,
+        })
+}
+
+/// Whether the `part of` directive of a part names the library including it, by URI or by name
+fn part_belongs(
+    claim: &PartClaim,
+    part_of: &parser::PartOf,
+    package_name: &str,
+    dir_entries: &mut DirEntries,
+    library_names: &HashMap<FileId, String>,
+) -> bool {
+    match part_of {
+        parser::PartOf::Uri(uri) => claim
+            .part
+            .resolve(uri, package_name)
+            .and_then(|x| dir_entries.on_disk(&x))
+            .is_some_and(|x| x == claim.library),
+        parser::PartOf::Name(name) => library_names.get(&claim.library) == Some(name),
+    }
+}
+
+/// Gets the source of a generated file that nothing reaches and why it is stale, `None` when it
+/// isn't generated or its source still generates it
+fn stale_generated(
+    file: &Path,
+    suffixes: &[String],
+    orphaned_parts: &HashMap<FileId, (parser::PartOf, usize)>,
+) -> Option<(PathBuf, &'static str)> {
+    let source = generated::source_of(file, suffixes)?;
+    if !source.is_file() {
+        Some((source, "its source file doesn't exist"))
+    } else if orphaned_parts.contains_key(&FileId::new(file)) {
+        Some((source, "its source file doesn't include it as a part"))
+    } else {
+        None
+    }
+}
+
+/// Finds the files with a `part of` directive that no library includes.
+///
+/// The files that weren't walked are read for their `part` directives as well, so the parts of
+/// unreachable libraries are reported as unreferenced rather than orphaned.
+fn find_orphaned_parts(
+    files: &[PathBuf],
+    package_name: &str,
+    extracted_data: &mut ExtractData,
+) -> HashMap<FileId, (parser::PartOf, usize)> {
+    let mut claimed: HashSet<FileId> = extracted_data
+        .part_claims
+        .iter()
+        .map(|x| x.part.clone())
+        .collect();
+    let mut parts = HashMap::new();
+    for file in files {
+        let id = FileId::new(file);
+        if extracted_data.referenced_files.contains(&id)
+            || extracted_data.test_referenced_files.contains(&id)
+        {
+            continue;
+        }
+        let Ok(source) = std::fs::read_to_string(file) else {
+            continue;
+        };
+        for directive in parser::directives(&source) {
+            match directive.dart {
+                Some(parser::DartFile::Part(uri)) => {
+                    if let Some(part) = id
+                        .resolve(&uri, package_name)
+                        .and_then(|x| extracted_data.dir_entries.on_disk(&x))
                     {
-                        extracted_data
-                            .referenced_files
-                            .insert(file.parse_dot().unwrap().to_path_buf());
-                        extract_data(
-                            &file.parse_dot().unwrap().to_path_buf(),
-                            package_name,
-                            extracted_data,
-                            deps,
-                            assets,
-                            args,
-                        )?;
+                        claimed.insert(part);
                     }
                 }
-                parser::DartFile::Package(name, mut path) => {
-                    // package imports
-                    if name == package_name {
-                        path.insert_str(0, "lib");
-                        let path = path.replace("%20", " ");
-                        let file = Path::new(&path);
-                        if !extracted_data
-                            .referenced_files
-                            .contains(&file.to_path_buf())
-                        {
-                            extracted_data.referenced_files.insert(file.to_path_buf());
-                            extract_data(
-                                &file.to_path_buf(),
-                                package_name,
-                                extracted_data,
-                                deps,
-                                assets,
-                                args,
-                            )?;
-                        }
-                    } else {
-                        // referenced_packages.push(DartFile::Package(name, path));
-                        // Remove deps used in referenced files
-                        deps.retain(|x| x != &name);
-                    }
+                Some(parser::DartFile::PartOf(part_of)) => {
+                    parts.insert(id.clone(), (part_of, directive.line));
                 }
-                parser::DartFile::Part(value) => {
-                    // part files
-                    let mut file = file_path.clone();
-                    file.set_file_name(value);
-                    extracted_data.referenced_files.insert(file);
+                _ => {}
+            }
+        }
+    }
+    parts.retain(|id, _| !claimed.contains(id));
+    parts
+}
+
+/// Replaces the URI of the directive starting on the line with the fixed one
+fn fix_directive(file: &FileId, line: usize, uri: &str, fixed: &str) -> anyhow::Result<()> {
+    let source = std::fs::read_to_string(file)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", file, e))?;
+    let tokens = lexer::tokenize(&source);
+    let Some(token) = tokens.iter().find(|x| {
+        x.line >= line && x.kind == lexer::TokenKind::String && parser::unquote(x.text) == uri
+    }) else {
+        warn!(
+            "Directive with '{}' not found in {} line {}",
+            uri, file, line
+        );
+        return Ok(());
+    };
+    let start = token.offset + token.text.find(uri).unwrap_or_default();
+    let mut contents = source[..start].to_string();
+    contents.push_str(fixed);
+    contents.push_str(&source[start + uri.len()..]);
+    std::fs::write(file, contents)?;
+    info!("Fixed the case of '{}' in {} line {}", uri, file, line);
+    Ok(())
+}
+
+/// Reads the file and checks its contents, returning the local files it references.
+///
+/// The contents are only checked for files reached from the app, the tests only need to know
+/// which files they reference.
+fn extract_data(
+    file_path: &FileId,
+    root: Root,
+    package_name: &str,
+    extracted_data: &mut ExtractData,
+    deps: &mut Vec<String>,
+    assets: &mut Vec<OsStringWithStr>,
+    args: &cli::Options,
+) -> anyhow::Result<References> {
+    let source = std::fs::read_to_string(file_path)
+        .map_err(|e| anyhow::anyhow!("Failed to read {}: {}", file_path, e))?;
+    let tokens = lexer::tokenize(&source);
+    // Only the code is checked so commented out code and text in strings aren't mistaken for
+    // directives or references
+    let contents = lexer::code(&source, &tokens, args.doc_comments);
+    let mut references = References::default();
+    for directive in parser::parse_tokens(&tokens) {
+        for dart in directive.targets(&extracted_data.conditions) {
+            if let parser::DartFile::Library(Some(name)) = dart {
+                extracted_data
+                    .library_names
+                    .insert(file_path.clone(), name.clone());
+            }
+            let Some(uri) = dart.uri() else {
+                continue;
+            };
+            let Some(file) = file_path.resolve(uri, package_name) else {
+                if let parser::Uri::Package(name, _) = uri
+                    && root == Root::App
+                {
+                    // Remove deps used in referenced files
+                    deps.retain(|x| x != name);
                 }
-                parser::DartFile::Export(path) => {
-                    let file = path.replace("%20", " ");
-                    let file = Path::new(&file);
-                    let file = file_path.parent().unwrap().join(file);
-                    if !extracted_data
-                        .referenced_files
-                        .contains(&file.to_path_buf())
-                    {
-                        extracted_data
-                            .referenced_files
-                            .insert(file.parse_dot().unwrap().to_path_buf());
-                        extract_data(
-                            &file.parse_dot().unwrap().to_path_buf(),
-                            package_name,
-                            extracted_data,
-                            deps,
-                            assets,
-                            args,
-                        )?;
-                    }
+                continue;
+            };
+            let Some(real) = extracted_data.dir_entries.on_disk(&file) else {
+                extracted_data.broken_references.push(BrokenReference {
+                    source: file_path.clone(),
+                    line: directive.line,
+                    directive: format!("{} '{}'", dart.keyword(), uri),
+                    uri: uri.to_string(),
+                    target: file,
+                });
+                continue;
+            };
+            if real != file {
+                extracted_data.case_mismatches.push(CaseMismatch {
+                    source: file_path.clone(),
+                    line: directive.line,
+                    keyword: dart.keyword(),
+                    uri: uri.to_string(),
+                    fixed: file_path.fix_case(uri, &real).to_string(),
+                    target: real.clone(),
+                });
+            }
+            match dart {
+                parser::DartFile::Part(_) => {
+                    extracted_data.part_claims.push(PartClaim {
+                        library: file_path.clone(),
+                        part: real.clone(),
+                    });
+                    references.parts.push(real)
                 }
+                _ => references.files.push(real),
             }
         }
     }
 
+    if root == Root::Test {
+        return Ok(references);
+    }
+
     let mut remove = false;
     let mut referenced_asset_files = HashSet::with_capacity(10);
     for asset in assets.iter() {
@@ -283,7 +965,7 @@ fn extract_data(
     }
 
     if args.labels {
-        let s = all_localisation(&contents);
+        let s = all_localisation(&contents, &extracted_data.class_name);
         if let Ok((_, keys)) = s {
             for key in keys {
                 extracted_data.labels_referenced.insert(key.to_owned());
@@ -292,20 +974,603 @@ fn extract_data(
     }
 
     if args.loc
-        && let Ok((_, r)) = locator::locator(&contents)
+        && let Ok((_, r)) = locator::locator(&contents, &extracted_data.locator_name)
     {
         for reg in r {
             match reg {
                 locator::Locator::Register(s) => {
-                    extracted_data.locators.entry(s).or_insert(false);
+                    extracted_data
+                        .locators
+                        .entry(s)
+                        .or_default()
+                        .registered_in
+                        .get_or_insert_with(|| file_path.clone());
                 }
                 locator::Locator::Get(s) => {
-                    extracted_data.locators.insert(s, true);
+                    extracted_data.locators.entry(s).or_default().used = true;
                 }
                 _ => {}
             }
         }
     }
 
-    Ok(())
+    Ok(references)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn write(dir: &Path, name: &str, contents: &str) -> FileId {
+        std::fs::write(dir.join(name), contents).unwrap();
+        FileId::new(dir.join(name))
+    }
+
+    fn extract(file: &FileId) -> (References, ExtractData) {
+        let mut extracted_data = ExtractData::new();
+        let references = extract_data(
+            file,
+            Root::App,
+            "app",
+            &mut extracted_data,
+            &mut Vec::new(),
+            &mut Vec::new(),
+            &cli::Options::default(),
+        )
+        .unwrap();
+        (references, extracted_data)
+    }
+
+    #[test]
+    fn test_broken_reference() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let main = write(
+            dir,
+            "main.dart",
+            "import 'a.dart';\nexport 'missing.dart';\npart 'main.g.dart';\n",
+        );
+        let a = write(dir, "a.dart", "");
+        let (references, extracted_data) = extract(&main);
+        // The broken references aren't walked and the rest still are
+        assert_eq!(references.files, vec![a]);
+        assert!(references.parts.is_empty());
+        let broken: Vec<_> = extracted_data
+            .broken_references
+            .iter()
+            .map(|x| (x.line, x.directive.as_str(), x.target.clone()))
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                (
+                    2,
+                    "export 'missing.dart'",
+                    FileId::new(dir.join("missing.dart"))
+                ),
+                (
+                    3,
+                    "part 'main.g.dart'",
+                    FileId::new(dir.join("main.g.dart"))
+                ),
+            ]
+        );
+        assert!(
+            extracted_data
+                .broken_references
+                .iter()
+                .all(|x| x.source == main)
+        );
+    }
+
+    #[test]
+    fn test_case_mismatch() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let main = write(
+            dir,
+            "main.dart",
+            "import 'a.dart';\nimport 'Widget.dart';\n",
+        );
+        let a = write(dir, "a.dart", "");
+        let widget = write(dir, "widget.dart", "");
+        let (references, extracted_data) = extract(&main);
+        // The file is walked with the casing on disk
+        assert_eq!(references.files, vec![a, widget.clone()]);
+        assert_eq!(extracted_data.case_mismatches.len(), 1);
+        let mismatch = &extracted_data.case_mismatches[0];
+        assert_eq!((mismatch.line, mismatch.uri.as_str()), (2, "Widget.dart"));
+        assert_eq!(mismatch.fixed, "widget.dart");
+        assert_eq!(mismatch.target, widget);
+    }
+
+    #[test]
+    fn test_fix_directive() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let source = "import 'A.dart';\nexport 'A.dart'\n    show A;\n// 'A.dart'\n";
+        let fixed = "import 'A.dart';\nexport 'a.dart'\n    show A;\n// 'A.dart'\n";
+        let file = write(dir, "main.dart", source);
+        fix_directive(&file, 2, "A.dart", "a.dart").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), fixed);
+        // Comments aren't rewritten, so nothing changes when the URI isn't found from the line on
+        fix_directive(&file, 4, "A.dart", "a.dart").unwrap();
+        assert_eq!(std::fs::read_to_string(&file).unwrap(), fixed);
+    }
+
+    #[test]
+    fn test_walk() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let main = write(dir, "main.dart", "import 'a.dart';\npart 'main.g.dart';\n");
+        let part = write(dir, "main.g.dart", "part of 'main.dart';\n");
+        let a = write(dir, "a.dart", "import 'b.dart';\n");
+        let b = write(dir, "b.dart", "");
+        let test = write(dir, "a_test.dart", "import 'b.dart';\nimport 'c.dart';\n");
+        let c = write(dir, "c.dart", "import 'd.dart';\n");
+        let d = write(dir, "d.dart", "");
+        write(dir, "dead.dart", "import 'd.dart';\n");
+        let mut extracted_data = ExtractData::new();
+        for (roots, root) in [
+            (vec![main.clone()], Root::App),
+            (vec![test.clone()], Root::Test),
+        ] {
+            walk(
+                &roots,
+                root,
+                "app",
+                &mut extracted_data,
+                &mut Vec::new(),
+                &mut Vec::new(),
+                &cli::Options::default(),
+            )
+            .unwrap();
+        }
+        assert_eq!(
+            extracted_data.referenced_files,
+            HashSet::from([main.clone(), part, a, b.clone()])
+        );
+        assert_eq!(
+            extracted_data.test_referenced_files,
+            HashSet::from([test.clone(), b.clone(), c, d.clone()])
+        );
+        let referenced_by = referenced_by(&extracted_data.references);
+        let reached = |file: &FileId, roots: &FileId, reachable: &HashSet<FileId>| {
+            reached_from(
+                file,
+                &HashSet::from([roots.clone()]),
+                reachable,
+                &referenced_by,
+            )
+        };
+        assert_eq!(
+            reached(&b, &main, &extracted_data.referenced_files),
+            vec![main.clone().into_path()]
+        );
+        assert_eq!(
+            reached(&d, &test, &extracted_data.test_referenced_files),
+            vec![test.clone().into_path()]
+        );
+        // Only the references of the files the walk reached are followed back
+        assert_eq!(
+            reached(&d, &main, &extracted_data.referenced_files),
+            Vec::<PathBuf>::new()
+        );
+    }
+
+    #[test]
+    fn test_reached_by() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let main = write(dir, "main.dart", "import 'app.dart';\n");
+        let app = write(dir, "app.dart", "");
+        let feature = write(dir, "feature.dart", "");
+        let screen = write(dir, "screen.dart", "import 'feature.dart';\n");
+        let a_test = write(
+            dir,
+            "a_test.dart",
+            "import 'app.dart';\nimport 'feature.dart';\n",
+        );
+        let b_test = write(dir, "b_test.dart", "import 'screen.dart';\n");
+        let dead = write(dir, "dead.dart", "import 'app.dart';\n");
+        let mut extracted_data = ExtractData::new();
+        for (roots, root) in [
+            (vec![main.clone()], Root::App),
+            (vec![b_test.clone(), a_test.clone()], Root::Test),
+        ] {
+            walk(
+                &roots,
+                root,
+                "app",
+                &mut extracted_data,
+                &mut Vec::new(),
+                &mut Vec::new(),
+                &cli::Options::default(),
+            )
+            .unwrap();
+        }
+        // Files the tests reach as well are still part of the app
+        assert_eq!(extracted_data.reached_by(&app), Some(Root::App));
+        assert_eq!(extracted_data.reached_by(&feature), Some(Root::Test));
+        assert_eq!(extracted_data.reached_by(&screen), Some(Root::Test));
+        assert_eq!(extracted_data.reached_by(&dead), None);
+        // Every test reaching a test-only file is listed, in order
+        assert_eq!(
+            reached_from(
+                &feature,
+                &HashSet::from([a_test.clone(), b_test.clone()]),
+                &extracted_data.test_referenced_files,
+                &referenced_by(&extracted_data.references),
+            ),
+            vec![a_test.into_path(), b_test.into_path()]
+        );
+    }
+
+    #[test]
+    fn test_walk_parts() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let main = write(dir, "main.dart", "part 'screen.dart';\n");
+        write(
+            dir,
+            "screen.dart",
+            "part of 'main.dart';\nfinal client = http.Client();\n",
+        );
+        let mut extracted_data = ExtractData::new();
+        let mut deps = vec!["http".to_string(), "provider".to_string()];
+        walk(
+            &[main],
+            Root::App,
+            "app",
+            &mut extracted_data,
+            &mut deps,
+            &mut Vec::new(),
+            &cli::Options::default(),
+        )
+        .unwrap();
+        // The code of a part is checked along with its library
+        assert_eq!(deps, vec!["provider"]);
+    }
+
+    #[test]
+    fn test_registration_line() {
+        let source = "// locator.registerFactory<A>(() => A());\n\
+                      /* locator.registerFactory<A>(\n() => A()); */\n\
+                      // dart-unused:ignore locator\n\
+                      locator.registerFactory<A>(() => A());\n";
+        assert_eq!(registration_line(source, "A", false), Some(5));
+        assert!(suppress::is_line_ignored(source, 5, &["locator"]));
+        assert_eq!(registration_line(source, "B", false), None);
+    }
+
+    #[test]
+    fn test_part_belongs() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let main = write(dir, "main.dart", "library my.app;\npart 'main.g.dart';\n");
+        let part = write(dir, "main.g.dart", "part of 'main.dart';\n");
+        write(dir, "other.dart", "");
+        let (references, mut extracted_data) = extract(&main);
+        assert_eq!(references.parts, vec![part.clone()]);
+        let claim = &extracted_data.part_claims[0];
+        assert_eq!((&claim.library, &claim.part), (&main, &part));
+        for (part_of, belongs) in [
+            ("part of 'main.dart';", true),
+            ("part of 'other.dart';", false),
+            ("part of my.app;", true),
+            ("part of my.other;", false),
+        ] {
+            let (part_of, _) = read_part_of(part_of).unwrap();
+            assert_eq!(
+                part_belongs(
+                    claim,
+                    &part_of,
+                    "app",
+                    &mut extracted_data.dir_entries,
+                    &extracted_data.library_names
+                ),
+                belongs,
+                "{}",
+                part_of
+            );
+        }
+        assert_eq!(read_part_of("import 'a.dart';"), None);
+    }
+
+    /// Files of a project with a live and an unreachable library and their parts, along with
+    /// parts that no library includes
+    fn parts_project() -> (tempfile::TempDir, Vec<PathBuf>, ExtractData) {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let live = write(dir, "live.dart", "part 'live.g.dart';\n");
+        let live_part = write(dir, "live.g.dart", "part of 'live.dart';\n");
+        write(dir, "dead.dart", "part 'dead.g.dart';\n");
+        write(dir, "dead.g.dart", "part of 'dead.dart';\n");
+        write(dir, "renamed.dart", "");
+        write(dir, "renamed.g.dart", "part of 'renamed.dart';\n");
+        write(dir, "gone.freezed.dart", "part of 'gone.dart';\n");
+        write(dir, "injection.dart", "");
+        write(dir, "injection.config.dart", "");
+        let mut extracted_data = ExtractData::new();
+        extracted_data.referenced_files.insert(live.clone());
+        extracted_data.referenced_files.insert(live_part.clone());
+        extracted_data.part_claims.push(PartClaim {
+            library: live,
+            part: live_part,
+        });
+        let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+            .unwrap()
+            .flatten()
+            .map(|x| x.path())
+            .collect();
+        files.sort();
+        (project, files, extracted_data)
+    }
+
+    #[test]
+    fn test_orphaned_parts() {
+        let (project, files, mut extracted_data) = parts_project();
+        let dir = project.path();
+        let orphaned = find_orphaned_parts(&files, "app", &mut extracted_data);
+        let mut orphans: Vec<&FileId> = orphaned.keys().collect();
+        orphans.sort();
+        // The part of the unreachable library is left to the unreferenced files
+        assert_eq!(
+            orphans,
+            vec![
+                &FileId::new(dir.join("gone.freezed.dart")),
+                &FileId::new(dir.join("renamed.g.dart"))
+            ]
+        );
+    }
+
+    #[test]
+    fn test_stale_generated() {
+        let (project, files, mut extracted_data) = parts_project();
+        let dir = project.path();
+        let orphaned = find_orphaned_parts(&files, "app", &mut extracted_data);
+        let suffixes: Vec<String> = [".g.dart", ".freezed.dart", ".config.dart"]
+            .iter()
+            .map(|x| x.to_string())
+            .collect();
+        let stale = |name: &str| stale_generated(&dir.join(name), &suffixes, &orphaned);
+        assert_eq!(
+            stale("gone.freezed.dart"),
+            Some((dir.join("gone.dart"), "its source file doesn't exist"))
+        );
+        assert_eq!(
+            stale("renamed.g.dart"),
+            Some((
+                dir.join("renamed.dart"),
+                "its source file doesn't include it as a part"
+            ))
+        );
+        assert_eq!(stale("injection.config.dart"), None);
+        assert_eq!(stale("dead.g.dart"), None);
+        assert_eq!(stale("live.dart"), None);
+    }
+
+    /// Analyses the project in the directory, one at a time as the current directory is changed
+    /// during the analysis
+    fn analyse_dir(dir: &Path, args: cli::Options) -> anyhow::Result<AnalysisReport> {
+        static CURRENT_DIR: std::sync::Mutex<()> = std::sync::Mutex::new(());
+        let _lock = CURRENT_DIR.lock().unwrap_or_else(|e| e.into_inner());
+        let cwd = std::env::current_dir().unwrap();
+        let report = get_unreferenced_files(cli::Options {
+            path: dir.to_path_buf(),
+            ..args
+        });
+        // The current directory is changed back on failures as well
+        assert_eq!(std::env::current_dir().unwrap(), cwd);
+        report
+    }
+
+    #[test]
+    fn test_dependency_ignored() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let pubspec = r#"name: app
+dependencies:
+  my_pkg:
+    path: ../my_pkg
+  path: ^1.8.0 # dart-unused:ignore
+  remote:
+    git:
+      url: https://github.com/user/remote.git # dart-unused:ignore
+  url: ^1.0.0
+"#;
+        std::fs::write(dir.join("pubspec.yaml"), pubspec).unwrap();
+        std::fs::create_dir(dir.join("lib")).unwrap();
+        write(&dir.join("lib"), "main.dart", "void main() {}\n");
+        let report = analyse_dir(
+            dir,
+            cli::Options {
+                deps: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let mut deps: Vec<(&str, Option<usize>)> = report
+            .findings
+            .iter()
+            .filter(|x| x.category == Category::UnusedDependency)
+            .map(|x| (x.name.as_deref().unwrap(), x.line))
+            .collect();
+        deps.sort();
+        // The comment on the nested url key doesn't ignore the url package
+        assert_eq!(
+            deps,
+            vec![("my_pkg", Some(3)), ("remote", Some(6)), ("url", Some(9))]
+        );
+    }
+
+    #[test]
+    fn test_entries_outside_lib() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        std::fs::write(dir.join("pubspec.yaml"), "name: app\n").unwrap();
+        std::fs::create_dir(dir.join("bin")).unwrap();
+        let args = cli::Options {
+            entries: vec!["lib/main.dart".to_string()],
+            ..Default::default()
+        };
+        let error = analyse_dir(dir, args.clone()).unwrap_err();
+        assert_eq!(error.to_string(), "No entry points found");
+        // The executables are roots even when the entry points match nothing
+        write(&dir.join("bin"), "cli.dart", "void main() {}\n");
+        let report = analyse_dir(dir, args).unwrap();
+        assert_eq!(report.entries, vec![PathBuf::from("bin/cli.dart")]);
+    }
+
+    #[test]
+    fn test_package_with_executables() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        std::fs::write(dir.join("pubspec.yaml"), "name: app\n").unwrap();
+        std::fs::create_dir_all(dir.join("lib/src")).unwrap();
+        std::fs::create_dir(dir.join("bin")).unwrap();
+        write(&dir.join("lib/src"), "command.dart", "");
+        write(
+            &dir.join("bin"),
+            "cli.dart",
+            "import 'package:app/src/command.dart';\n",
+        );
+        // Without public libraries the package is still analysed from its executables
+        let report = analyse_dir(dir, cli::Options::default()).unwrap();
+        assert_eq!(report.entries, vec![PathBuf::from("bin/cli.dart")]);
+        assert!(report.findings.is_empty());
+    }
+
+    #[test]
+    fn test_invalid_arb() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        std::fs::write(dir.join("pubspec.yaml"), "name: app\n").unwrap();
+        std::fs::create_dir_all(dir.join("lib/l10n")).unwrap();
+        write(&dir.join("lib"), "main.dart", "void main() {}\n");
+        write(&dir.join("lib/l10n"), "intl_en.arb", "{\"title\": ");
+        let error = analyse_dir(
+            dir,
+            cli::Options {
+                labels: true,
+                ..Default::default()
+            },
+        )
+        .unwrap_err();
+        assert!(
+            error
+                .to_string()
+                .starts_with("Invalid ARB file \"lib/l10n/intl_en.arb\"")
+        );
+    }
+
+    #[test]
+    fn test_remove() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        let files = [
+            (
+                "pubspec.yaml",
+                "name: app\nflutter:\n  assets:\n    - assets/\n",
+            ),
+            (
+                "unused.config.yaml",
+                "files:\n  ignore:\n    - lib/legacy/**\n",
+            ),
+            ("assets/logo.png", ""),
+            ("assets/icons/old.png", ""),
+            ("lib/main.dart", "import 'widget.dart';\n"),
+            ("lib/widget.dart", ""),
+            ("lib/widget_part.dart", "part of 'widget.dart';\n"),
+            ("lib/dead.dart", ""),
+            ("lib/gone.freezed.dart", "part of 'gone.dart';\n"),
+            ("lib/helper.dart", ""),
+            (
+                "lib/kept.dart",
+                "// dart-unused:ignore-file\nclass Kept {}\n",
+            ),
+            ("lib/legacy/old.dart", ""),
+            ("lib/legacy/old.g.dart", "part of 'old_source.dart';\n"),
+            (
+                "test/helper_test.dart",
+                "import 'package:app/helper.dart';\n",
+            ),
+        ];
+        for (name, contents) in files {
+            let path = dir.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(path, contents).unwrap();
+        }
+        let report = analyse_dir(
+            dir,
+            cli::Options {
+                remove: true,
+                assets: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let reported = |category: Category, name: &str| {
+            report
+                .findings
+                .iter()
+                .any(|x| x.category == category && x.path == Path::new(name))
+        };
+        assert!(reported(Category::UnreferencedAsset, "assets/logo.png"));
+        assert!(reported(Category::TestOnlyFile, "lib/helper.dart"));
+        assert!(reported(Category::StaleGenerated, "lib/legacy/old.g.dart"));
+        let removed: Vec<&str> = files
+            .iter()
+            .map(|(name, _)| *name)
+            .filter(|x| !dir.join(x).exists())
+            .collect();
+        // Unreferenced assets, test-only files and ignored files are reported but never removed
+        assert_eq!(
+            removed,
+            vec![
+                "assets/icons/old.png",
+                "lib/widget_part.dart",
+                "lib/dead.dart",
+                "lib/gone.freezed.dart",
+            ]
+        );
+    }
+
+    #[test]
+    fn test_labels_class_name() {
+        let project = tempfile::tempdir().unwrap();
+        let dir = project.path();
+        std::fs::write(dir.join("pubspec.yaml"), "name: app\n").unwrap();
+        std::fs::write(
+            dir.join("unused.config.yaml"),
+            "labels:\n  class_name: L10n\n",
+        )
+        .unwrap();
+        std::fs::create_dir_all(dir.join("lib/l10n")).unwrap();
+        write(
+            &dir.join("lib"),
+            "main.dart",
+            "final title = L10n.of(context)!.title;\n",
+        );
+        write(
+            &dir.join("lib/l10n"),
+            "app_en.arb",
+            r#"{"title": "Title", "unused": "Unused"}"#,
+        );
+        let report = analyse_dir(
+            dir,
+            cli::Options {
+                labels: true,
+                ..Default::default()
+            },
+        )
+        .unwrap();
+        let labels: Vec<&str> = report
+            .findings
+            .iter()
+            .filter(|x| x.category == Category::UnusedLabel)
+            .map(|x| x.name.as_deref().unwrap())
+            .collect();
+        assert_eq!(labels, vec!["unused"]);
+    }
 }
@@ -3,32 +3,21 @@ use nom::{
     branch::alt,
     bytes::complete::{tag, take_till, take_until},
     character::complete::{alphanumeric1, multispace0},
+    combinator::opt,
     multi::many0,
 };
-use std::sync::OnceLock;
-
-static INSTANCE: OnceLock<String> = OnceLock::new();
-
-/// Set the class name to be used for localisation
-///
-/// NOTE: this needs to be set before calling `all_localisation`
-pub fn set_class_name(class_name: &str) -> anyhow::Result<()> {
-    INSTANCE
-        .set(class_name.to_string())
-        .expect("Failed to set class name");
-    Ok(())
-}
 
-/// Parse all localisation keys from a string
-pub fn all_localisation(input: &str) -> IResult<&str, Vec<&str>> {
-    many0(localisation).parse(input)
+/// Parse all localisation keys from a string, `class_name` is the class holding the localisations
+pub fn all_localisation<'a>(input: &'a str, class_name: &str) -> IResult<&'a str, Vec<&'a str>> {
+    many0(|x| localisation(x, class_name)).parse(input)
 }
 
-/// Parse a single localisation key from a string
-pub fn localisation(input: &str) -> IResult<&str, &str> {
+/// Parse a single localisation key from a string, `class_name` is the class holding the
+/// localisations
+pub fn localisation<'a>(input: &'a str, class_name: &str) -> IResult<&'a str, &'a str> {
     let (remaining, (_, _, _, _, _, _, _, _, key)) = (
-        take_until(INSTANCE.get().unwrap().as_str()),
-        tag(INSTANCE.get().unwrap().as_str()),
+        take_until(class_name),
+        tag(class_name),
         multispace0,
         tag("."),
         multispace0,
@@ -47,6 +36,9 @@ fn of_context(input: &str) -> IResult<&str, &str> {
         tag("of("),
         alphanumeric1, // Generally 'context' but not guaranteed
         tag(")"),
+        // gen-l10n's `of` returns a nullable instance
+        multispace0,
+        opt(tag("!")),
     )
         .parse(input)?;
     Ok((remaining, ""))
@@ -71,133 +63,129 @@ pub(crate) fn is_alphanumeric_or_underscore(input: &str) -> IResult<&str, &str>
 
 #[cfg(test)]
 mod tests {
-    use std::sync::Once;
-
     use super::*;
 
     #[test]
     fn test_localisation() {
-        Once::new().call_once(|| {
-            let _ = INSTANCE.set("S".to_string());
-        });
         let input = "S.of(context).app_name";
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = "S.current.app_name";
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = "S.maybeOf(context)?.app_name";
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
+        assert_eq!(expected, actual);
+
+        let input = "AppLocalizations.of(context)!.title";
+        let expected = "title";
+        let (_, actual) = localisation(input, "AppLocalizations").unwrap();
+        assert_eq!(expected, actual);
+
+        let input = "S.of(context).app_name AppLocalizations.of(context).title";
+        let expected = vec!["title"];
+        let (_, actual) = all_localisation(input, "AppLocalizations").unwrap();
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn multi_line_test() {
-        Once::new().call_once(|| {
-            let _ = INSTANCE.set("S".to_string());
-        });
         let input = r#"""S.of(context)
             .app_name"""#;
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#"""S.current
         .app_name"""#;
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#"""S.maybeOf(context)
         ?.app_name"""#;
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#"""S
         .of(context)
             .app_name"""#;
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#"""S
         .current
         .app_name"""#;
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#"""S
         .maybeOf(context)
         ?.app_name"""#;
         let expected = "app_name";
-        let (_, actual) = localisation(input).unwrap();
+        let (_, actual) = localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn test_multiple() {
-        Once::new().call_once(|| {
-            let _ = INSTANCE.set("S".to_string());
-        });
         let input = r#""S.of(context).app_name
         S.of(context).app_name""#;
         let expected = vec!["app_name", "app_name"];
-        let (_, actual) = all_localisation(input).unwrap();
+        let (_, actual) = all_localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#""S.current.app_name
         S.current.app_name""#;
         let expected = vec!["app_name", "app_name"];
-        let (_, actual) = all_localisation(input).unwrap();
+        let (_, actual) = all_localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#""S.maybeOf(context)?.app_name
         S.maybeOf(context)?.app_name""#;
         let expected = vec!["app_name", "app_name"];
-        let (_, actual) = all_localisation(input).unwrap();
+        let (_, actual) = all_localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
         let input = r#""S.of(context).app_name, S.of(context)
         .app_name
         S.maybeOf(context)?.app_name""#;
         let expected = vec!["app_name", "app_name", "app_name"];
-        let (_, actual) = all_localisation(input).unwrap();
+        let (_, actual) = all_localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
     }
 
     #[test]
     fn test_multiple_as_if_labels() {
-        Once::new().call_once(|| {
-            let _ = INSTANCE.set("S".to_string());
-        });
         let input = r#""t: S.of(context).app_name,
         k: S.of(context).app_name""#;
         let expected = vec!["app_name", "app_name"];
-        let (_, actual) = all_localisation(input).unwrap();
+        let (_, actual) = all_localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#""t: S.current.app_name,
         K:S.current.app_name""#;
         let expected = vec!["app_name", "app_name"];
-        let (_, actual) = all_localisation(input).unwrap();
+        let (_, actual) = all_localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
 
         let input = r#""t:S.maybeOf(context)?.app_name
         e:S.maybeOf(context)?.app_name""#;
         let expected = vec!["app_name", "app_name"];
-        let (_, actual) = all_localisation(input).unwrap();
+        let (_, actual) = all_localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
         let input = r#""d: S.of(context).app_name, k:S.of(context)
         .app_name
         s: S.maybeOf(context)?.app_name""#;
         let expected = vec!["app_name", "app_name", "app_name"];
-        let (_, actual) = all_localisation(input).unwrap();
+        let (_, actual) = all_localisation(input, "S").unwrap();
         assert_eq!(expected, actual);
     }
 }
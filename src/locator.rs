@@ -13,6 +13,25 @@ use nom::{
 
 use crate::localisation::is_alphanumeric_or_underscore;
 
+/// Name of the GetIt instance used when none has been configured
+pub const DEFAULT_NAME: &str = "locator";
+
+/// Finds the name of the variable the GetIt instance is assigned to, e.g. `getIt` in
+/// `final getIt = GetIt.instance;`
+pub fn find_name(contents: &str) -> Option<String> {
+    contents.lines().find_map(|line| {
+        let (declaration, value) = line.split_once('=')?;
+        let value = value.trim().trim_end_matches(';').trim();
+        if value != "GetIt.instance" && value != "GetIt.I" {
+            return None;
+        }
+        let name = declaration.split_whitespace().last()?;
+        name.chars()
+            .all(|c| c.is_alphanumeric() || c == '_' || c == '$')
+            .then(|| name.to_string())
+    })
+}
+
 #[derive(Debug, PartialEq)]
 pub enum Locator {
     Register(String),
@@ -32,24 +51,30 @@ fn register_locator(input: &str) -> IResult<&str, Locator> {
     Ok((rest, Locator::Register(class.to_string())))
 }
 
-fn find_locator(input: &str) -> IResult<&str, ()> {
-    let (r, _) = (take_until("locator."), tag("locator.")).parse(input)?;
+fn find_locator<'a>(input: &'a str, name: &str) -> IResult<&'a str, ()> {
+    let pattern = format!("{}.", name);
+    let (r, _) = (take_until(pattern.as_str()), tag(pattern.as_str())).parse(input)?;
     Ok((r, ()))
 }
 
-fn find_locator_alt(input: &str) -> IResult<&str, ()> {
-    let (r, _) = (take_until("locator<"), tag("locator<")).parse(input)?;
+fn find_locator_alt<'a>(input: &'a str, name: &str) -> IResult<&'a str, ()> {
+    let pattern = format!("{}<", name);
+    let (r, _) = (take_until(pattern.as_str()), tag(pattern.as_str())).parse(input)?;
     Ok((r, ()))
 }
 
-fn get_locator(input: &str) -> IResult<&str, Locator> {
-    let (s, (_, l)) = (find_locator, alt((import, register_locator, get, get_alt))).parse(input)?;
+fn get_locator<'a>(input: &'a str, name: &str) -> IResult<&'a str, Locator> {
+    let (s, (_, l)) = (
+        |x| find_locator(x, name),
+        alt((import, register_locator, get, get_alt)),
+    )
+        .parse(input)?;
     Ok((s, l))
 }
 
-fn get_locator_alt(input: &str) -> IResult<&str, Locator> {
+fn get_locator_alt<'a>(input: &'a str, name: &str) -> IResult<&'a str, Locator> {
     let (s, (_, l)) = (
-        find_locator_alt,
+        |x| find_locator_alt(x, name),
         alt((import, register_locator, get, get_alt)),
     )
         .parse(input)?;
@@ -61,15 +86,16 @@ fn import(input: &str) -> IResult<&str, Locator> {
     Ok((s, Locator::Import))
 }
 
-/// Parses multiple locator patterns from the input string
+/// Parses multiple locator patterns from the input string, `name` is the variable holding the
+/// GetIt instance
 ///
 /// Patterns can be of the form:
 /// - `locator.register...<GetIt>(() => ...);`
 /// - `locator.get<GetIt>();`
 /// - `locator<GetIt>();`
-pub fn locator(input: &str) -> IResult<&str, Vec<Locator>> {
-    let (r1, l) = many0(get_locator).parse(input)?;
-    let (r2, x) = many0(get_locator_alt).parse(input)?;
+pub fn locator<'a>(input: &'a str, name: &str) -> IResult<&'a str, Vec<Locator>> {
+    let (r1, l) = many0(|x| get_locator(x, name)).parse(input)?;
+    let (r2, x) = many0(|x| get_locator_alt(x, name)).parse(input)?;
     let mut s = l;
     s.extend(x);
     if r1.len() > r2.len() {
@@ -99,6 +125,16 @@ fn get_alt(input: &str) -> IResult<&str, Locator> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_find_name() {
+        assert_eq!(
+            find_name("import 'package:get_it/get_it.dart';\n\nfinal getIt = GetIt.instance;\n"),
+            Some("getIt".to_string())
+        );
+        assert_eq!(find_name("GetIt sl = GetIt.I;"), Some("sl".to_string()));
+        assert_eq!(find_name("final x = GetIt.asNewInstance();"), None);
+    }
+
     #[test]
     fn test_locator() {
         let input = r#"register<GetIt>();"#;
@@ -123,44 +159,57 @@ mod tests {
     #[test]
     fn test_locator_parent() {
         let input = r#"locator.register<GetIt>();"#;
-        let result = get_locator(input);
+        let result = get_locator(input, DEFAULT_NAME);
         assert_eq!(result, Ok(("();", Locator::Register("GetIt".to_string()))));
     }
 
     #[test]
     fn test_locator_singleton_parent() {
         let input = r#"locator.registerLazySingleton<GetIt>();"#;
-        let result = get_locator(input);
+        let result = get_locator(input, DEFAULT_NAME);
         assert_eq!(result, Ok(("();", Locator::Register("GetIt".to_string()))));
     }
 
     #[test]
     fn test_locator_factory_parent() {
         let input = r#"locator.registerFactory<GetIt>();"#;
-        let result = get_locator(input);
+        let result = get_locator(input, DEFAULT_NAME);
         assert_eq!(result, Ok(("();", Locator::Register("GetIt".to_string()))));
     }
 
     #[test]
     fn test_get_locator() {
         let input = r#"locator.get<GetIt>();"#;
-        let result = get_locator(input);
+        let result = get_locator(input, DEFAULT_NAME);
         assert_eq!(result, Ok(("();", Locator::Get("GetIt".to_string()))));
     }
 
     #[test]
     fn test_get_locator_alt() {
         let input = r#"locator<GetIt>();"#;
-        let result = get_locator_alt(input);
+        let result = get_locator_alt(input, DEFAULT_NAME);
         assert_eq!(result, Ok((">();", Locator::Get("GetIt".to_string()))));
     }
 
+    #[test]
+    fn test_locator_name() {
+        let input = "getIt.registerFactory<A>();\nlocator<B>();\ngetIt<C>();";
+        let (_, result) = locator(input, "getIt").unwrap();
+        assert_eq!(
+            result,
+            vec![
+                Locator::Register("A".to_string()),
+                Locator::Get("C".to_string())
+            ]
+        );
+    }
+
     #[test]
     fn test_get_locator_in_register() {
         let input = r#"  locator.registerFactory<CreditApplicationContractBloc>(
       () => CreditApplicationContractBloc(locator.get<DownloadContractUseCase>(),
        locator<DownloadContractsUseCase>()));"#;
-        let result = locator(input);
+        let result = locator(input, DEFAULT_NAME);
         assert_eq!(
             result,
             Ok((
@@ -191,7 +240,7 @@ mod tests {
       domain.updateChatCacheUseCase,
       appConfig.testMode));"#;
 
-        let result = locator(input);
+        let result = locator(input, DEFAULT_NAME);
         assert_eq!(
             result,
             Ok((
@@ -335,7 +384,7 @@ Future<void> serviceOperationsLocator(
 }
 "#;
 
-        let result = locator(input);
+        let result = locator(input, DEFAULT_NAME);
 
         assert_eq!(
             result,
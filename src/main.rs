@@ -1,12 +1,18 @@
-use dart_unused::{cli::Options, get_unreferenced_files};
+use dart_unused::{
+    cli::Options,
+    effective_config,
+    format::{Format, render},
+    get_unreferenced_files, init_config,
+    report::{AnalysisReport, Check},
+};
 use log::LevelFilter;
 use simplelog::{
     ColorChoice, CombinedLogger, Config, ConfigBuilder, TermLogger, TerminalMode, WriteLogger,
 };
 
-use std::{fs::File, path::PathBuf};
+use std::{fs::File, path::PathBuf, process::ExitCode};
 
-use clap::Parser;
+use clap::{Parser, Subcommand};
 
 #[derive(Debug, Parser, Clone)]
 #[clap(
@@ -15,7 +21,15 @@ use clap::Parser;
     long_about = "Check for unreferenced files in a Dart project. This tool checks for unreferenced assets, dependencies, and dart files in a Dart project by default. You can also remove unreferenced files by using the --remove flag. You can specify what to check by using the flags --assets, --deps, and --dart."
 )]
 pub struct Args {
-    #[arg(short, long, help = "Path to the Dart project")]
+    #[command(subcommand)]
+    pub command: Option<Command>,
+    #[arg(
+        short,
+        long,
+        global = true,
+        default_value = ".",
+        help = "Path to the Dart project"
+    )]
     pub path: PathBuf,
     #[arg(long, help = "Remove all unreferenced items discovered")]
     pub remove: bool,
@@ -29,16 +43,86 @@ pub struct Args {
     pub labels: bool,
     #[arg(long, help = "List items registered in locator but not used")]
     pub loc: bool,
+    #[arg(
+        short,
+        long,
+        help = "Entry point of the app relative to the project, can be repeated and accepts glob patterns. Defaults to lib/main.dart, or the lib/main_*.dart flavours without it"
+    )]
+    pub entry: Vec<String>,
     #[arg(short, long, help = "Enable verbose logging")]
     pub verbose: bool,
-    // #[arg(long, short)]
-    // pub format: bool,
-    // #[arg(long, short)]
-    // pub warn: bool,
+    #[arg(
+        short,
+        long,
+        value_enum,
+        default_value_t = Format::Text,
+        help = "Format to output the findings in"
+    )]
+    pub format: Format,
+    #[arg(
+        short,
+        long,
+        help = "Output the findings as warnings so they never fail the run"
+    )]
+    pub warn: bool,
+    #[arg(
+        long,
+        value_enum,
+        value_delimiter = ',',
+        help = "Comma separated checks whose findings fail the run. Defaults to all checks"
+    )]
+    pub fail_on: Vec<Check>,
+    #[arg(
+        short,
+        long,
+        global = true,
+        help = "Config file to use instead of unused.config.yaml in the project root"
+    )]
+    pub config: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Only report the findings that aren't in this baseline file"
+    )]
+    pub baseline: Option<PathBuf>,
+    #[arg(long, help = "Write the current findings to this baseline file")]
+    pub write_baseline: Option<PathBuf>,
+    #[arg(
+        long,
+        help = "Check the doc comments as well as the code for references to assets, labels and locators"
+    )]
+    pub doc_comments: bool,
+    #[arg(
+        long = "condition",
+        value_delimiter = ',',
+        help = "Comma separated conditions, e.g. dart.library.io, that hold when resolving conditional imports. By default every URI is followed"
+    )]
+    pub conditions: Vec<String>,
+    #[arg(
+        long,
+        help = "Rewrite the imports, exports and parts whose path differs in case from the file"
+    )]
+    pub fix_case: bool,
     #[arg(short, long, short, help = "Output the results to a file")]
     pub output: bool,
 }
 
+#[derive(Debug, Subcommand, Clone)]
+pub enum Command {
+    /// Check the config of the project
+    Config {
+        #[arg(
+            long,
+            help = "Print the effective config with the configs it extends merged in"
+        )]
+        print: bool,
+    },
+    /// Write a starter unused.config.yaml based on what is found in the project
+    Init {
+        #[arg(long, help = "Overwrite the existing config")]
+        force: bool,
+    },
+}
+
 impl From<Args> for Options {
     fn from(val: Args) -> Self {
         Self {
@@ -46,13 +130,58 @@ impl From<Args> for Options {
             deps: val.deps,
             labels: val.labels,
             loc: val.loc,
+            entries: val.entry,
             path: val.path,
             remove: val.remove,
+            warn: val.warn,
+            fail_on: val.fail_on,
+            doc_comments: val.doc_comments,
+            conditions: val.conditions,
+            fix_case: val.fix_case,
+            // Resolved now as the current directory changes to the project
+            baseline: val.baseline.map(|x| std::path::absolute(&x).unwrap_or(x)),
+            config: val.config.map(|x| std::path::absolute(&x).unwrap_or(x)),
+            write_baseline: val
+                .write_baseline
+                .map(|x| std::path::absolute(&x).unwrap_or(x)),
         }
     }
 }
 
-fn main() -> anyhow::Result<()> {
+/// Exit code when the run fails because of the findings
+const EXIT_FINDINGS: u8 = 1;
+/// Exit code when the tool itself fails
+const EXIT_FAILURE: u8 = 2;
+
+fn main() -> ExitCode {
+    ExitCode::from(exit_code(std::panic::catch_unwind(run)))
+}
+
+/// Maps the outcome of the run to its exit code, printing the error of a failed run.
+///
+/// Failures are returned as errors, a panic is a bug that is still reported by the default hook
+/// and only needs to be mapped to the failure code
+fn exit_code(outcome: std::thread::Result<anyhow::Result<u8>>) -> u8 {
+    match outcome {
+        Ok(Ok(code)) => code,
+        Ok(Err(e)) => {
+            eprintln!("Error: {:#}", e);
+            EXIT_FAILURE
+        }
+        Err(_) => EXIT_FAILURE,
+    }
+}
+
+/// Gets the exit code of a finished analysis
+fn report_code(report: &AnalysisReport) -> u8 {
+    if report.has_errors() {
+        EXIT_FINDINGS
+    } else {
+        0
+    }
+}
+
+fn run() -> anyhow::Result<u8> {
     let config = ConfigBuilder::new()
         .set_time_level(log::LevelFilter::Off)
         .build();
@@ -62,18 +191,91 @@ fn main() -> anyhow::Result<()> {
     } else {
         LevelFilter::Info
     };
+    // Keep stdout clean for the machine readable formats and the subcommands
+    let terminal_mode = if args.format.is_machine_readable() || args.command.is_some() {
+        TerminalMode::Stderr
+    } else {
+        TerminalMode::Mixed
+    };
+    let format = args.format;
     if args.output {
         CombinedLogger::init(vec![
-            TermLogger::new(log_level, config, TerminalMode::Mixed, ColorChoice::Auto),
+            TermLogger::new(log_level, config, terminal_mode, ColorChoice::Auto),
             WriteLogger::new(
                 log_level,
                 Config::default(),
-                File::create("dart-unused.log").unwrap(),
+                File::create("dart-unused.log")
+                    .map_err(|e| anyhow::anyhow!("Failed to create dart-unused.log: {}", e))?,
             ),
         ])?;
     } else {
-        TermLogger::init(log_level, config, TerminalMode::Mixed, ColorChoice::Auto)?;
+        TermLogger::init(log_level, config, terminal_mode, ColorChoice::Auto)?;
+    }
+
+    let command = args.command.clone();
+    let options: Options = args.into();
+    match command {
+        Some(Command::Config { print }) => {
+            let config = effective_config(&options)?;
+            if print {
+                println!("{}", config.to_yaml()?);
+            } else {
+                log::info!("Config is valid");
+            }
+            return Ok(0);
+        }
+        Some(Command::Init { force }) => {
+            init_config(&options, force)?;
+            return Ok(0);
+        }
+        None => {}
+    }
+    let report = get_unreferenced_files(options.clone())?;
+    if let Some(output) = render(&report, &options, format)? {
+        println!("{}", output);
+    }
+    Ok(report_code(&report))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use dart_unused::report::{Category, Finding, Severity};
+
+    fn report(severities: &[Severity]) -> AnalysisReport {
+        let findings = severities
+            .iter()
+            .map(|severity| Finding {
+                severity: *severity,
+                ..Finding::new(Category::UnreferencedFile, PathBuf::from("lib/a.dart"), "")
+            })
+            .collect();
+        AnalysisReport {
+            findings,
+            ..Default::default()
+        }
     }
 
-    get_unreferenced_files(args.into())
+    #[test]
+    fn test_report_code() {
+        assert_eq!(report_code(&report(&[])), 0);
+        assert_eq!(
+            report_code(&report(&[Severity::Warning, Severity::Info])),
+            0
+        );
+        assert_eq!(
+            report_code(&report(&[Severity::Warning, Severity::Error])),
+            EXIT_FINDINGS
+        );
+    }
+
+    #[test]
+    fn test_exit_code() {
+        assert_eq!(exit_code(Ok(Ok(EXIT_FINDINGS))), EXIT_FINDINGS);
+        assert_eq!(
+            exit_code(Ok(Err(anyhow::anyhow!("Path does not exist")))),
+            EXIT_FAILURE
+        );
+        assert_eq!(exit_code(Err(Box::new("panic"))), EXIT_FAILURE);
+    }
 }
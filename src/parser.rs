@@ -1,307 +1,756 @@
-/// This module contains the parser for Dart files.
-/// It provides functions to parse different types of Dart file statements, such as imports, packages, and parts.
-/// The main entry point is the `dart_file` function, which takes a string input and returns a parsed `DartFile` enum.
-/// The `DartFile` enum represents different types of Dart file statements, including imports, packages, and parts.
-/// The module also includes conversion implementations for `TryFrom<&str>` and `TryFrom<&DartFile>`, which allow converting between `&str` and `DartFile` types.
-/// Additionally, there are several helper functions used by the parser, such as `quote`, `no_colons_in_input`, and `take_until_quote`.
-/// The module also includes unit tests for the parser functions.
-use nom::{
-    IResult, Parser,
-    branch::alt,
-    bytes::complete::{tag, take_until1},
-    character::complete::multispace1,
-    combinator::map_res,
-};
+/// This module contains the parser for the directives of Dart files.
+/// The directives are parsed from the tokens of the whole file, so directives split over multiple lines, preceded by
+/// annotations or comments, or indented are all found. The main entry point is the `directives` function, which returns
+/// the `DartFile` directives of the file along with the line they start on.
+use crate::lexer::{BOM, Token, TokenKind, tokenize};
 
 #[derive(Debug, PartialEq, Clone, Eq, PartialOrd, Ord)]
 pub enum DartFile {
-    Import(String),
-    Package(String, String),
-    Part(String),
-    Export(String),
+    Import(Uri, Combinators),
+    Part(Uri),
+    /// `part of` directive of a part file
+    PartOf(PartOf),
+    Export(Uri, Combinators),
+    /// `library` header with its name if it has one
+    Library(Option<String>),
 }
 
-impl TryFrom<&str> for DartFile {
-    type Error = &'static str;
-
-    fn try_from(value: &str) -> Result<Self, Self::Error> {
-        match dart_file(value) {
-            Ok((_, dart)) => {
-                if let DartFile::Import(path) = &dart
-                    && path.contains(":")
-                {
-                    return Err("Package imports are not supported");
-                }
-                Ok(dart)
-            }
-            Err(_) => Err("Failed to parse dart file"),
+impl DartFile {
+    /// The keyword the directive starts with
+    pub fn keyword(&self) -> &'static str {
+        match self {
+            DartFile::Import(..) => "import",
+            DartFile::Part(_) => "part",
+            DartFile::PartOf(_) => "part of",
+            DartFile::Export(..) => "export",
+            DartFile::Library(_) => "library",
         }
     }
-}
-
-impl TryFrom<&DartFile> for DartFile {
-    type Error = &'static str;
 
-    fn try_from(value: &DartFile) -> Result<Self, Self::Error> {
-        log::info!("Parsing: {:?}", value);
-        if let DartFile::Import(path) = &value
-            && path.contains(":")
-        {
-            return Err("Package imports are not supported");
+    /// The URI of the file the directive refers to
+    pub fn uri(&self) -> Option<&Uri> {
+        match self {
+            DartFile::Import(uri, _) | DartFile::Export(uri, _) | DartFile::Part(uri) => Some(uri),
+            DartFile::PartOf(_) | DartFile::Library(_) => None,
         }
-        Ok(value.clone())
     }
 }
 
-/// Parses a single or multiple quotes (either single or double quotes).
-fn quote(input: &str) -> IResult<&str, &str> {
-    alt((tag("'"), tag("\""))).parse(input)
+/// URI of an import, export or part, URIs of other schemes such as `dart:` aren't files so they
+/// aren't parsed
+#[derive(Debug, PartialEq, Clone, Eq, PartialOrd, Ord)]
+pub enum Uri {
+    /// Path relative to the file with the directive
+    Relative(String),
+    /// `package:` URI, the package name and the path within its `lib/` starting with `/`
+    Package(String, String),
 }
 
-/// Checks if the input string contains a colon.
-fn no_colons_in_input(input: &str) -> IResult<&str, &str> {
-    if input.contains(":") {
-        return Err(nom::Err::Error(nom::error::Error::new(
-            input,
-            nom::error::ErrorKind::NoneOf,
-        )));
+impl std::fmt::Display for Uri {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Uri::Relative(path) => write!(f, "{}", path),
+            Uri::Package(name, path) => write!(f, "package:{}{}", name, path),
+        }
     }
-    Ok(("", input))
 }
 
-/// Parses an import statement and returns a `DartFile::Import` variant.
-fn import_parser(input: &str) -> IResult<&str, DartFile> {
-    let (remaining, (_, _, _, path)) =
-        (tag("import"), multispace1, quote, take_until_quote).parse(input)?;
-    no_colons_in_input(path)?;
-
-    Ok((remaining, DartFile::Import(path.to_string())))
+impl Uri {
+    fn parse(uri: String) -> Option<Uri> {
+        if let Some(package) = uri.strip_prefix("package:") {
+            let (name, path) = package.split_at(package.find('/')?);
+            Some(Uri::Package(name.to_string(), path.to_string()))
+        } else if uri.contains(':') {
+            None
+        } else {
+            Some(Uri::Relative(uri))
+        }
+    }
 }
 
-/// Parses an import statement using the `import_parser` function and converts the result to `DartFile` using `TryFrom`.
-fn import(input: &str) -> IResult<&str, DartFile> {
-    let mut parser = map_res(import_parser, DartFile::try_from);
-
-    parser.parse(input)
+/// The library a part file belongs to
+#[derive(Debug, PartialEq, Clone, Eq, PartialOrd, Ord)]
+pub enum PartOf {
+    /// URI of the library, e.g. `part of 'a.dart'`
+    Uri(Uri),
+    /// Name of the library, e.g. `part of my.app`
+    Name(String),
 }
 
-/// Parses an export statement and returns a `DartFile::Export` variant.
-fn export_parser(input: &str) -> IResult<&str, DartFile> {
-    let (remaining, (_, _, _, path)) =
-        (tag("export"), multispace1, quote, take_until_quote).parse(input)?;
-    no_colons_in_input(path)?;
+impl std::fmt::Display for PartOf {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PartOf::Uri(uri) => write!(f, "'{}'", uri),
+            PartOf::Name(name) => write!(f, "{}", name),
+        }
+    }
+}
 
-    Ok((remaining, DartFile::Export(path.to_string())))
+/// How the names of an import or export are brought into scope
+#[derive(Debug, Default, PartialEq, Clone, Eq, PartialOrd, Ord)]
+pub struct Combinators {
+    /// `as` prefix of an import
+    pub prefix: Option<String>,
+    /// Whether the import is `deferred`
+    pub deferred: bool,
+    /// Names listed with `show`
+    pub show: Vec<String>,
+    /// Names listed with `hide`
+    pub hide: Vec<String>,
 }
 
-/// Parses an export statement using the `import_parser` function and converts the result to `DartFile` using `TryFrom`.
-fn export(input: &str) -> IResult<&str, DartFile> {
-    export_parser(input)
+/// URI of a conditional import or export used when the condition holds, e.g.
+/// `if (dart.library.io) 'io_impl.dart'`
+#[derive(Debug, PartialEq, Clone, Eq)]
+pub struct Configuration {
+    /// Name of the environment declaration tested, e.g. `dart.library.io`
+    pub name: String,
+    /// Value it is compared with, `true` when there is no comparison
+    pub value: String,
+    pub dart: DartFile,
 }
 
-/// Parses a package statement and returns a `DartFile::Package` variant.
-fn package(input: &str) -> IResult<&str, DartFile> {
-    let (remaining, (_, _, _, _, name, path)) = (
-        tag("import"),
-        multispace1,
-        quote,
-        tag("package:"),
-        take_until1("/"),
-        take_until_quote,
-    )
-        .parse(input)?;
-    Ok((
-        remaining,
-        DartFile::Package(name.to_string(), path.to_string()),
-    ))
+impl Configuration {
+    /// Whether the condition holds for the given conditions, which are either names that are
+    /// `true` or `name=value` pairs
+    pub fn holds(&self, conditions: &[String]) -> bool {
+        conditions
+            .iter()
+            .any(|condition| match condition.split_once('=') {
+                Some((name, value)) => name == self.name && value == self.value,
+                None => condition == &self.name && self.value == "true",
+            })
+    }
 }
 
-/// Parses a part statement and returns a `DartFile::Part` variant.
-fn part(input: &str) -> IResult<&str, DartFile> {
-    let (remaining, (_, _, _, value)) =
-        (tag("part"), multispace1, quote, take_until_quote).parse(input)?;
+/// A directive and the line its keyword is on
+#[derive(Debug, PartialEq, Clone, Eq)]
+pub struct Directive {
+    /// The directive, `None` for a conditional import or export whose default URI isn't a file,
+    /// e.g. `dart:io`
+    pub dart: Option<DartFile>,
+    pub line: usize,
+    /// The configurations of a conditional import or export
+    pub configurations: Vec<Configuration>,
+}
 
-    Ok((remaining, DartFile::Part(value.to_string())))
+impl Directive {
+    /// The files the directive refers to.
+    ///
+    /// Without conditions every URI of a conditional import or export is used. With conditions
+    /// only the URI Dart would pick is used, the first configuration whose condition holds or the
+    /// default URI.
+    pub fn targets(&self, conditions: &[String]) -> Vec<&DartFile> {
+        if conditions.is_empty() {
+            self.dart
+                .iter()
+                .chain(self.configurations.iter().map(|x| &x.dart))
+                .collect()
+        } else {
+            let selected = match self.configurations.iter().find(|x| x.holds(conditions)) {
+                Some(configuration) => Some(&configuration.dart),
+                None => self.dart.as_ref(),
+            };
+            selected.into_iter().collect()
+        }
+    }
 }
 
-/// Parses a Dart file statement and returns a `DartFile` variant.
+/// Parses the directives of a Dart file.
 ///
 /// ```rust
-/// use dart_unused::parser::{DartFile, dart_file};
+/// use dart_unused::parser::{Combinators, DartFile, Uri, directives};
 ///
-/// let input = "import 'flutter/material.dart';";
-/// let expected = DartFile::Import("flutter/material.dart".to_string());
-/// let result = dart_file(input);
-/// assert_eq!(result, Ok(("';", expected)));
+/// let input = "import 'package:flutter/material.dart'\n    show Colors;";
+/// let result = directives(input);
+/// assert_eq!(
+///     result[0].dart,
+///     Some(DartFile::Import(
+///         Uri::Package("flutter".to_string(), "/material.dart".to_string()),
+///         Combinators {
+///             show: vec!["Colors".to_string()],
+///             ..Default::default()
+///         }
+///     ))
+/// );
 /// ```
 ///
 /// ```rust
-/// use dart_unused::parser::{DartFile, dart_file};
+/// use dart_unused::parser::{DartFile, Uri, directives};
 ///
-/// let input = "import 'package:flutter/material.dart';";
-/// let expected = DartFile::Package("flutter".to_string(), "/material.dart".to_string());
-/// let result = dart_file(input);
-/// assert_eq!(result, Ok(("';", expected)));
+/// let input = "@Deprecated('Use b.dart')\nexport 'a.dart';";
+/// let result = directives(input);
+/// assert_eq!(
+///     result[0].dart,
+///     Some(DartFile::Export(Uri::Relative("a.dart".to_string()), Default::default()))
+/// );
+/// assert_eq!(result[0].line, 2);
 /// ```
 ///
 /// ```rust
-/// use dart_unused::parser::{DartFile, dart_file};
-///
-/// let input = "part 'material.g.dart';";
-/// let expected = DartFile::Part("material.g.dart".to_string());
-/// let result = dart_file(input);
-/// assert_eq!(result, Ok(("';", expected)));
-/// ```
-///
-/// ```rust
-/// use dart_unused::parser::{DartFile, dart_file};
+/// use dart_unused::parser::directives;
 ///
 /// let input = "import 'dart:io';";
-/// let result = dart_file(input);
-/// assert!(result.is_err());
-/// ```
-///
-/// ```rust
-/// use dart_unused::parser::{DartFile, dart_file};
-///
-/// let input = "import 'flutter/material.dart';";
-/// let expected = DartFile::Import("flutter/material.dart".to_string());
-/// let result = dart_file(input);
-/// assert_eq!(result, Ok(("';", expected)));
+/// assert!(directives(input).is_empty());
 /// ```
-pub fn dart_file(input: &str) -> IResult<&str, DartFile> {
-    alt((package, import, part, export)).parse(input)
+pub fn directives(input: &str) -> Vec<Directive> {
+    parse_tokens(&tokenize(input))
+}
+
+/// Parses the directives from the tokens of a Dart file
+pub fn parse_tokens(tokens: &[Token]) -> Vec<Directive> {
+    let tokens: Vec<&Token> = tokens.iter().filter(|x| !x.kind.is_comment()).collect();
+    let mut parser = Parser { tokens, pos: 0 };
+    // The `#!` script tag of a script is on the first line, before any directive
+    if parser
+        .peek()
+        .is_some_and(|x| (x.offset == 0 || x.offset == BOM.len_utf8()) && x.text == "#")
+        && parser.tokens.get(1).is_some_and(|x| x.text == "!")
+    {
+        while parser.peek().is_some_and(|x| x.line == 1) {
+            parser.pos += 1;
+        }
+    }
+    let mut directives = Vec::new();
+    // Only statements at the top level can be directives
+    let mut depth = 0;
+    let mut statement_start = true;
+    while let Some(token) = parser.peek() {
+        if depth == 0 && statement_start && token.kind == TokenKind::Word {
+            let start = parser.pos;
+            if let Some((dart, configurations)) = parser.directive() {
+                directives.push(Directive {
+                    dart,
+                    line: token.line,
+                    configurations,
+                });
+                continue;
+            }
+            parser.pos = start;
+        }
+        parser.pos += 1;
+        match token.text {
+            "{" | "(" | "[" => depth += 1,
+            "}" | ")" | "]" => depth -= 1,
+            _ => {}
+        }
+        statement_start = depth == 0 && matches!(token.text, ";" | "}");
+        if depth == 0 && token.text == "@" {
+            parser.annotation();
+            statement_start = true;
+        }
+    }
+    directives
+}
+
+struct Parser<'a, 'b> {
+    tokens: Vec<&'b Token<'a>>,
+    pos: usize,
+}
+
+impl<'a, 'b> Parser<'a, 'b> {
+    fn peek(&self) -> Option<&'b Token<'a>> {
+        self.tokens.get(self.pos).copied()
+    }
+
+    fn next(&mut self) -> Option<&'b Token<'a>> {
+        let token = self.peek();
+        self.pos += 1;
+        token
+    }
+
+    /// Moves past the token if it has the given text
+    fn eat(&mut self, text: &str) -> bool {
+        if self.peek().is_some_and(|x| x.text == text) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn word(&mut self) -> Option<&'a str> {
+        let token = self.peek().filter(|x| x.kind == TokenKind::Word)?;
+        self.pos += 1;
+        Some(token.text)
+    }
+
+    fn uri(&mut self) -> Option<String> {
+        let token = self.peek().filter(|x| x.kind == TokenKind::String)?;
+        self.pos += 1;
+        Some(unquote(token.text).to_string())
+    }
+
+    /// Parses a name such as `my.app`
+    fn dotted_name(&mut self) -> Option<String> {
+        let mut name = vec![self.word()?];
+        while self.eat(".") {
+            name.push(self.word()?);
+        }
+        Some(name.join("."))
+    }
+
+    /// Moves past the rest of an annotation after the `@`
+    fn annotation(&mut self) {
+        self.word();
+        while self.eat(".") {
+            self.word();
+        }
+        if self.eat("(") {
+            self.skip_group();
+        }
+    }
+
+    /// Moves past the tokens up to and including the bracket closing the one just passed
+    fn skip_group(&mut self) {
+        let mut depth = 1;
+        while depth > 0
+            && let Some(token) = self.next()
+        {
+            match token.text {
+                "(" | "[" | "{" => depth += 1,
+                ")" | "]" | "}" => depth -= 1,
+                _ => {}
+            }
+        }
+    }
+
+    /// Parses the directive starting at the current token along with its configurations
+    fn directive(&mut self) -> Option<(Option<DartFile>, Vec<Configuration>)> {
+        match self.word()? {
+            "library" => {
+                let name = self.dotted_name();
+                self.eat(";")
+                    .then_some((Some(DartFile::Library(name)), Vec::new()))
+            }
+            "part" if self.eat("of") => {
+                let part_of = match self.uri() {
+                    Some(uri) => PartOf::Uri(Uri::parse(uri)?),
+                    None => PartOf::Name(self.dotted_name()?),
+                };
+                self.eat(";")
+                    .then_some((Some(DartFile::PartOf(part_of)), Vec::new()))
+            }
+            "part" => {
+                let uri = Uri::parse(self.uri()?)?;
+                self.eat(";")
+                    .then_some((Some(DartFile::Part(uri)), Vec::new()))
+            }
+            keyword @ ("import" | "export") => {
+                let uri = self.uri()?;
+                let configurations = self.configurations()?;
+                let combinators = self.combinators()?;
+                let configurations = configurations
+                    .into_iter()
+                    .filter_map(|(name, value, uri)| {
+                        Some(Configuration {
+                            name,
+                            value,
+                            dart: dart_file(keyword, uri, combinators.clone())?,
+                        })
+                    })
+                    .collect::<Vec<_>>();
+                // The configurations are kept when the default URI isn't a file, e.g. `dart:io`
+                let dart = dart_file(keyword, uri, combinators);
+                (dart.is_some() || !configurations.is_empty()).then_some((dart, configurations))
+            }
+            _ => None,
+        }
+    }
+
+    /// Parses the `if (name == 'value') 'uri'` configurations of a conditional import or export
+    fn configurations(&mut self) -> Option<Vec<(String, String, String)>> {
+        let mut configurations = Vec::new();
+        while self.eat("if") {
+            if !self.eat("(") {
+                return None;
+            }
+            let mut name = vec![self.word()?];
+            while self.eat(".") {
+                name.push(self.word()?);
+            }
+            let value = if self.eat("=") && self.eat("=") {
+                self.uri()?
+            } else {
+                "true".to_string()
+            };
+            if !self.eat(")") {
+                return None;
+            }
+            configurations.push((name.join("."), value, self.uri()?));
+        }
+        Some(configurations)
+    }
+
+    /// Parses the `deferred`, `as` and `show`/`hide` parts of an import or export up to the `;`
+    fn combinators(&mut self) -> Option<Combinators> {
+        let mut combinators = Combinators::default();
+        loop {
+            match self.next()?.text {
+                ";" => return Some(combinators),
+                "deferred" => combinators.deferred = true,
+                "as" => combinators.prefix = Some(self.word()?.to_string()),
+                keyword @ ("show" | "hide") => {
+                    let mut names = vec![self.word()?.to_string()];
+                    while self.eat(",") {
+                        names.push(self.word()?.to_string());
+                    }
+                    if keyword == "show" {
+                        combinators.show.extend(names);
+                    } else {
+                        combinators.hide.extend(names);
+                    }
+                }
+                _ => return None,
+            }
+        }
+    }
+}
+
+/// Gets the directive for the URI of an import or export
+fn dart_file(keyword: &str, uri: String, combinators: Combinators) -> Option<DartFile> {
+    let uri = Uri::parse(uri)?;
+    if keyword == "export" {
+        Some(DartFile::Export(uri, combinators))
+    } else {
+        Some(DartFile::Import(uri, combinators))
+    }
 }
 
-/// Parses a string until a quote is encountered (either single or double quotes).
-fn take_until_quote(input: &str) -> IResult<&str, &str> {
-    alt((take_until1("'"), take_until1("\""))).parse(input)
+/// Gets the contents of a string literal without the quotes or the `r` of raw strings
+pub(crate) fn unquote(text: &str) -> &str {
+    let text = text.strip_prefix('r').unwrap_or(text);
+    for quote in ["'''", "\"\"\"", "'", "\""] {
+        if let Some(inner) = text.strip_prefix(quote).and_then(|x| x.strip_suffix(quote)) {
+            return inner;
+        }
+    }
+    text
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    fn dart_files(input: &str) -> Vec<DartFile> {
+        directives(input)
+            .into_iter()
+            .filter_map(|x| x.dart)
+            .collect()
+    }
+
+    fn relative(path: &str) -> Uri {
+        Uri::Relative(path.to_string())
+    }
+
+    fn package(name: &str, path: &str) -> Uri {
+        Uri::Package(name.to_string(), path.to_string())
+    }
+
+    fn import(path: &str) -> DartFile {
+        DartFile::Import(relative(path), Combinators::default())
+    }
+
+    fn package_import(name: &str, path: &str) -> DartFile {
+        DartFile::Import(package(name, path), Combinators::default())
+    }
+
     #[test]
     fn test_import() {
-        let input = "import 'flutter/material.dart';";
-        let expected = DartFile::Import("flutter/material.dart".to_string());
-        let result = import(input);
-        assert_eq!(result, Ok(("';", expected)));
+        assert_eq!(
+            dart_files("import 'flutter/material.dart';"),
+            vec![import("flutter/material.dart")]
+        );
     }
 
     #[test]
     fn test_import_path() {
-        let input = "import './flutter/material.dart';";
-        let expected = DartFile::Import("./flutter/material.dart".to_string());
-        let result = import(input);
-        assert_eq!(result, Ok(("';", expected)));
+        assert_eq!(
+            dart_files("import './flutter/material.dart';"),
+            vec![import("./flutter/material.dart")]
+        );
     }
 
     #[test]
     fn test_import_relative() {
-        let input = "import '../flutter/material.dart';";
-        let expected = DartFile::Import("../flutter/material.dart".to_string());
-        let result = import(input);
-        assert_eq!(result, Ok(("';", expected)));
+        assert_eq!(
+            dart_files("import \"../flutter/material.dart\";"),
+            vec![import("../flutter/material.dart")]
+        );
     }
 
     #[test]
     fn test_import_failure() {
-        let input = "import 'dart:io';";
-        let result = import(input);
-        assert!(result.is_err());
+        assert!(dart_files("import 'dart:io';").is_empty());
     }
 
     #[test]
     fn test_package() {
-        let input = "import 'package:flutter/material.dart';";
-        let expected = DartFile::Package("flutter".to_string(), "/material.dart".to_string());
-        let result = package(input);
-        assert_eq!(result, Ok(("';", expected)));
+        assert_eq!(
+            dart_files("import 'package:flutter/material.dart';"),
+            vec![package_import("flutter", "/material.dart")]
+        );
     }
 
     #[test]
     fn test_part() {
-        let input = "part 'material.g.dart';";
-        let expected = DartFile::Part("material.g.dart".to_string());
-        let result = part(input);
-        assert_eq!(result, Ok(("';", expected)));
+        assert_eq!(
+            dart_files("part 'material.g.dart';"),
+            vec![DartFile::Part(relative("material.g.dart"))]
+        );
     }
 
     #[test]
-    fn test_dart_file_import() {
-        let input = "import 'flutter/material.dart';";
-        let expected = DartFile::Import("flutter/material.dart".to_string());
-        let result = dart_file(input);
-        assert_eq!(result, Ok(("';", expected)));
+    fn test_export() {
+        assert_eq!(
+            dart_files("export 'src/a.dart' show A hide B;"),
+            vec![DartFile::Export(
+                relative("src/a.dart"),
+                Combinators {
+                    show: vec!["A".to_string()],
+                    hide: vec!["B".to_string()],
+                    ..Default::default()
+                }
+            )]
+        );
+    }
+
+    #[test]
+    fn test_commented() {
+        assert!(dart_files("// import 'flutter/material.dart';").is_empty());
+        assert!(dart_files("// part 'material.g.dart';").is_empty());
+        assert!(dart_files("/* import 'package:flutter/material.dart'; */").is_empty());
+    }
+
+    #[test]
+    fn test_with_comment() {
+        assert_eq!(
+            dart_files("import 'flutter/material.dart'; // comment"),
+            vec![import("flutter/material.dart")]
+        );
+        assert_eq!(
+            dart_files("part 'material.g.dart'; // comment"),
+            vec![DartFile::Part(relative("material.g.dart"))]
+        );
+        assert_eq!(
+            dart_files("import /* a */ 'package:flutter/material.dart'; // comment"),
+            vec![package_import("flutter", "/material.dart")]
+        );
     }
 
     #[test]
-    fn test_dart_file_package() {
-        let input = "import 'package:flutter/material.dart';";
-        let expected = DartFile::Package("flutter".to_string(), "/material.dart".to_string());
-        let result = dart_file(input);
-        assert_eq!(result, Ok(("';", expected)));
+    fn test_multi_line() {
+        let input = r#"
+import 'package:foo/foo.dart'
+    show Bar, Baz;
+import 'b.dart' deferred as b;
+"#;
+        assert_eq!(
+            dart_files(input),
+            vec![
+                DartFile::Import(
+                    package("foo", "/foo.dart"),
+                    Combinators {
+                        show: vec!["Bar".to_string(), "Baz".to_string()],
+                        ..Default::default()
+                    }
+                ),
+                DartFile::Import(
+                    relative("b.dart"),
+                    Combinators {
+                        prefix: Some("b".to_string()),
+                        deferred: true,
+                        ..Default::default()
+                    }
+                ),
+            ]
+        );
     }
 
     #[test]
-    fn test_dart_file_part() {
-        let input = "part 'material.g.dart';";
-        let expected = DartFile::Part("material.g.dart".to_string());
-        let result = dart_file(input);
-        assert_eq!(result, Ok(("';", expected)));
+    fn test_annotated() {
+        let input = r#"
+/// The library
+@Deprecated('Use the new one')
+@pragma('vm:entry-point')
+library;
+
+@Deprecated("a;b")
+export 'a.dart';
+  import 'c.dart';
+"#;
+        let result = directives(input);
+        assert_eq!(
+            result
+                .iter()
+                .filter_map(|x| x.dart.clone())
+                .collect::<Vec<_>>(),
+            vec![
+                DartFile::Library(None),
+                DartFile::Export(relative("a.dart"), Combinators::default()),
+                import("c.dart"),
+            ]
+        );
+        assert_eq!(
+            result.iter().map(|x| x.line).collect::<Vec<_>>(),
+            vec![5, 8, 9]
+        );
     }
 
     #[test]
-    fn test_dart_file_import_error() {
-        let input = "import 'dart:io';";
-        let result = dart_file(input);
-        assert!(result.is_err());
+    fn test_conditional() {
+        let input = r#"
+import 'stub.dart'
+    if (dart.library.io) 'io_impl.dart'
+    if (dart.library.js_interop) 'package:app/web_impl.dart'
+    if (dart.library.html) 'dart:html'
+    as platform;
+export 'a.dart' if (dart.library.ui == "false") 'b.dart';
+"#;
+        let result = directives(input);
+        assert_eq!(result.len(), 2);
+        let prefixed = Combinators {
+            prefix: Some("platform".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            result[0].targets(&[]),
+            vec![
+                &DartFile::Import(relative("stub.dart"), prefixed.clone()),
+                &DartFile::Import(relative("io_impl.dart"), prefixed.clone()),
+                &DartFile::Import(package("app", "/web_impl.dart"), prefixed.clone()),
+            ]
+        );
+        assert_eq!(
+            result[0].targets(&["dart.library.js_interop".to_string()]),
+            vec![&DartFile::Import(
+                package("app", "/web_impl.dart"),
+                prefixed.clone()
+            )]
+        );
+        assert_eq!(
+            result[0].targets(&["dart.library.ui".to_string()]),
+            vec![&DartFile::Import(relative("stub.dart"), prefixed)]
+        );
+        assert_eq!(result[1].configurations[0].value, "false");
+        assert_eq!(
+            result[1].targets(&["dart.library.ui=false".to_string()]),
+            vec![&DartFile::Export(
+                relative("b.dart"),
+                Combinators::default()
+            )]
+        );
     }
 
     #[test]
-    fn test_commented_import() {
-        let input = "// import 'flutter/material.dart';";
-        let result = dart_file(input);
-        assert!(result.is_err());
+    fn test_conditional_sdk_default() {
+        let input = "import 'dart:io' if (dart.library.html) 'package:app/src/web.dart';";
+        let result = directives(input);
+        assert_eq!(result.len(), 1);
+        assert_eq!(result[0].dart, None);
+        let web = DartFile::Import(package("app", "/src/web.dart"), Combinators::default());
+        assert_eq!(result[0].targets(&[]), vec![&web]);
+        assert_eq!(
+            result[0].targets(&["dart.library.html".to_string()]),
+            vec![&web]
+        );
+        assert!(
+            result[0]
+                .targets(&["dart.library.io".to_string()])
+                .is_empty()
+        );
     }
 
     #[test]
-    fn test_import_with_comment() {
-        let input = "import 'flutter/material.dart'; // comment";
-        let expected = DartFile::Import("flutter/material.dart".to_string());
-        let result = dart_file(input);
-        assert_eq!(result, Ok(("'; // comment", expected)));
+    fn test_package_export_and_part() {
+        let input = r#"
+export 'package:app/src/foo.dart';
+export 'package:other/other.dart' hide Other;
+part 'package:app/src/foo.g.dart';
+"#;
+        assert_eq!(
+            dart_files(input),
+            vec![
+                DartFile::Export(package("app", "/src/foo.dart"), Combinators::default()),
+                DartFile::Export(
+                    package("other", "/other.dart"),
+                    Combinators {
+                        hide: vec!["Other".to_string()],
+                        ..Default::default()
+                    }
+                ),
+                DartFile::Part(package("app", "/src/foo.g.dart")),
+            ]
+        );
+        assert!(dart_files("export 'dart:async';").is_empty());
     }
 
     #[test]
-    fn test_commented_part() {
-        let input = "// part 'material.g.dart';";
-        let result = dart_file(input);
-        assert!(result.is_err());
+    fn test_uri_display() {
+        assert_eq!(relative("../a.dart").to_string(), "../a.dart");
+        assert_eq!(
+            package("app", "/src/a.dart").to_string(),
+            "package:app/src/a.dart"
+        );
+        assert_eq!(DartFile::Part(relative("a.g.dart")).keyword(), "part");
     }
 
     #[test]
-    fn test_part_with_comment() {
-        let input = "part 'material.g.dart'; // comment";
-        let expected = DartFile::Part("material.g.dart".to_string());
-        let result = dart_file(input);
-        assert_eq!(result, Ok(("'; // comment", expected)));
+    fn test_part_of() {
+        assert_eq!(
+            dart_files("part of 'a.dart';"),
+            vec![DartFile::PartOf(PartOf::Uri(relative("a.dart")))]
+        );
+        assert_eq!(
+            dart_files("// ignore_for_file: x\npart of my.app;"),
+            vec![DartFile::PartOf(PartOf::Name("my.app".to_string()))]
+        );
+        assert_eq!(dart_files("part of 'package:app/a.dart';")[0].uri(), None);
     }
 
     #[test]
-    fn test_commented_package() {
-        let input = "// import 'package:flutter/material.dart';";
-        let result = dart_file(input);
-        assert!(result.is_err());
+    fn test_library_name() {
+        assert_eq!(
+            dart_files("library my.app;"),
+            vec![DartFile::Library(Some("my.app".to_string()))]
+        );
     }
 
     #[test]
-    fn test_package_with_comment() {
-        let input = "import 'package:flutter/material.dart'; // comment";
-        let expected = DartFile::Package("flutter".to_string(), "/material.dart".to_string());
-        let result = dart_file(input);
-        assert_eq!(result, Ok(("'; // comment", expected)));
+    fn test_script_tag() {
+        let input = "#!/usr/bin/env dart\nimport 'package:app/src/cli.dart';\npart 'a.dart';\n";
+        assert_eq!(
+            dart_files(input),
+            vec![
+                package_import("app", "/src/cli.dart"),
+                DartFile::Part(relative("a.dart")),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_bom() {
+        assert_eq!(
+            dart_files("\u{feff}import \"a.dart\";\nimport 'b.dart';"),
+            vec![import("a.dart"), import("b.dart"),]
+        );
+        assert_eq!(
+            dart_files("\u{feff}#!/usr/bin/env dart\npart 'a.dart';"),
+            vec![DartFile::Part(relative("a.dart"))]
+        );
+    }
+
+    #[test]
+    fn test_not_directives() {
+        let input = r#"
+const s = '''
+import 'a.dart';
+''';
+void main() {
+  final part = 'b.dart';
+  import('c.dart');
+}
+"#;
+        assert!(dart_files(input).is_empty());
     }
 }
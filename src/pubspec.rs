@@ -4,10 +4,46 @@ use glob::glob;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
 
+use crate::config::{self, Config};
+
 pub fn get_package_details() -> anyhow::Result<PubspecSchema> {
-    let pubspec = std::fs::read_to_string("pubspec.yaml").expect("Failed to read pubspec.yaml");
+    let pubspec = std::fs::read_to_string("pubspec.yaml")
+        .map_err(|e| anyhow::anyhow!("Failed to read pubspec.yaml: {}", e))?;
+
+    // the dart_unused section is checked for unknown keys, so say where they are
+    serde_yaml2::from_str(&pubspec).map_err(|e| {
+        anyhow::anyhow!(
+            "Invalid pubspec.yaml: {}",
+            config::with_position(&pubspec, e.to_string())
+        )
+    })
+}
 
-    serde_yaml2::from_str(&pubspec).map_err(|e| e.into())
+/// Finds the line the dependency is declared on in the `dependencies` section of the pubspec contents.
+///
+/// Only the keys at the first indentation level of the section are dependencies,
+/// deeper keys such as `path:` or `git:` belong to the dependency above them.
+pub fn dependency_line(contents: &str, name: &str) -> Option<usize> {
+    let key = format!("{}:", name);
+    let mut in_dependencies = false;
+    let mut indent = None;
+    for (index, line) in contents.lines().enumerate() {
+        if line.trim().is_empty() || line.trim_start().starts_with('#') {
+            continue;
+        }
+        if !line.starts_with(char::is_whitespace) {
+            let section = line.split('#').next().unwrap_or_default().trim_end();
+            in_dependencies = section == "dependencies:";
+            indent = None;
+        } else if in_dependencies {
+            let depth = line.len() - line.trim_start().len();
+            let level = *indent.get_or_insert(depth);
+            if depth == level && line.trim_start().starts_with(&key) {
+                return Some(index + 1);
+            }
+        }
+    }
+    None
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -53,6 +89,9 @@ pub struct PubspecSchema {
     pub flutter: Flutter,
     #[serde(default)]
     pub flutter_intl: FlutterIntl,
+    /// Config of dart-unused, used when there is no config file
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dart_unused: Option<Config>,
 }
 
 #[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
@@ -275,7 +314,7 @@ pub struct DeferredComponent {
     pub assets: Option<Vec<PathBuf>>,
 }
 
-#[derive(Debug, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[derive(Debug, Serialize, Deserialize, PartialEq, Eq)]
 pub struct FlutterIntl {
     #[serde(default)]
     pub enabled: bool,
@@ -291,6 +330,20 @@ pub struct FlutterIntl {
     pub localize_function_name: Option<String>,
 }
 
+/// The defaults of a pubspec without a flutter_intl section are the same as the ones of its keys
+impl Default for FlutterIntl {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            class_name: default_class_name(),
+            output_dir: default_output_dir(),
+            arb_dir: default_arb_dir(),
+            main_locale: default_main_locale(),
+            localize_function_name: None,
+        }
+    }
+}
+
 fn default_class_name() -> String {
     "AppLocalizations".to_string()
 }
@@ -329,6 +382,45 @@ mod tests {
     use super::*;
     use serde::de::value::Error;
 
+    #[test]
+    fn test_dependency_line() {
+        let yaml = r#"
+name: test_app
+dependencies:
+  flutter:
+    sdk: flutter
+  # comment
+  http: ^1.0.0
+dev_dependencies:
+  mocktail: ^1.0.0
+"#;
+        assert_eq!(dependency_line(yaml, "flutter"), Some(4));
+        assert_eq!(dependency_line(yaml, "http"), Some(7));
+        assert_eq!(dependency_line(yaml, "mocktail"), None);
+        assert_eq!(dependency_line(yaml, "missing"), None);
+    }
+
+    #[test]
+    fn test_dependency_line_skips_nested_keys() {
+        let yaml = r#"
+name: test_app
+dependencies: # runtime
+  my_pkg:
+    path: ../my_pkg
+  path: ^1.8.0
+  remote:
+    git:
+      url: https://github.com/user/remote.git
+  url: ^1.0.0
+dev_dependencies:
+  git: ^1.0.0
+"#;
+        assert_eq!(dependency_line(yaml, "my_pkg"), Some(4));
+        assert_eq!(dependency_line(yaml, "path"), Some(6));
+        assert_eq!(dependency_line(yaml, "url"), Some(10));
+        assert_eq!(dependency_line(yaml, "git"), None);
+    }
+
     #[test]
     fn test_simple_dependencies() {
         let input = r#"
@@ -717,6 +809,8 @@ flutter:
         assert_eq!(pubspec.description, None);
         assert!(pubspec.dependencies.is_empty());
         assert!(pubspec.dev_dependencies.is_empty());
+        assert_eq!(pubspec.flutter_intl.class_name, "AppLocalizations");
+        assert_eq!(pubspec.flutter_intl.arb_dir, PathBuf::from("lib/l10n"));
     }
 
     // #[test]
@@ -0,0 +1,424 @@
+//! The findings of an analysis, which the output formats render.
+
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+/// The checks that can be run, each covering one or more categories
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, clap::ValueEnum)]
+#[serde(rename_all = "snake_case")]
+pub enum Check {
+    /// Unreferenced and test-only Dart files, broken references, case mismatches, part
+    /// mismatches, orphaned parts and stale generated files
+    Files,
+    /// Unreferenced and unregistered assets
+    Assets,
+    /// Unused dependencies
+    Deps,
+    /// Unused ARB keys
+    Labels,
+    /// Unused locator registrations
+    Loc,
+}
+
+/// How serious a finding is
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum Severity {
+    /// Fails the run
+    #[default]
+    Error,
+    /// Reported without failing the run
+    Warning,
+    /// Reported for information only
+    Info,
+    /// Not reported at all
+    Off,
+}
+
+impl Severity {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Severity::Error => "error",
+            Severity::Warning => "warning",
+            Severity::Info => "info",
+            Severity::Off => "off",
+        }
+    }
+}
+
+// Read from a plain string as the YAML parser expects enums to be mappings
+impl<'de> Deserialize<'de> for Severity {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let value = String::deserialize(deserializer)?;
+        match value.as_str() {
+            "error" => Ok(Severity::Error),
+            "warning" => Ok(Severity::Warning),
+            "info" => Ok(Severity::Info),
+            "off" => Ok(Severity::Off),
+            _ => Err(serde::de::Error::unknown_variant(
+                &value,
+                &["error", "warning", "info", "off"],
+            )),
+        }
+    }
+}
+
+/// The kind of problem a finding reports
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Category {
+    /// Asset registered in the pubspec but not referenced by any reachable file
+    UnreferencedAsset,
+    /// File in an asset directory that isn't registered in the pubspec
+    UnregisteredAsset,
+    /// Dependency that isn't used by any reachable file
+    UnusedDependency,
+    /// ARB key that isn't used by any reachable file
+    UnusedLabel,
+    /// Class registered in the locator but never retrieved
+    UnusedLocator,
+    /// Dart file in `lib/` only reachable from tests
+    TestOnlyFile,
+    /// Dart file in `lib/` not reachable from any entry point or test
+    UnreferencedFile,
+    /// Import, export or part of a file that doesn't exist
+    BrokenReference,
+    /// Import, export or part whose path differs in case from the file
+    CaseMismatch,
+    /// Part file whose `part of` names another library than the one including it
+    PartMismatch,
+    /// File with a `part of` directive that no library includes
+    OrphanedPart,
+    /// Generated file whose source file is gone or no longer includes it
+    StaleGenerated,
+}
+
+impl Category {
+    /// All the categories in the order they are reported
+    pub const ALL: [Category; 12] = [
+        Category::UnreferencedAsset,
+        Category::UnregisteredAsset,
+        Category::UnusedDependency,
+        Category::UnusedLabel,
+        Category::UnusedLocator,
+        Category::TestOnlyFile,
+        Category::UnreferencedFile,
+        Category::BrokenReference,
+        Category::CaseMismatch,
+        Category::PartMismatch,
+        Category::OrphanedPart,
+        Category::StaleGenerated,
+    ];
+
+    /// Stable identifier of the category, used as the rule id by the reporters
+    pub fn rule_id(&self) -> &'static str {
+        match self {
+            Category::UnreferencedAsset => "unreferenced-asset",
+            Category::UnregisteredAsset => "unregistered-asset",
+            Category::UnusedDependency => "unused-dependency",
+            Category::UnusedLabel => "unused-label",
+            Category::UnusedLocator => "unused-locator",
+            Category::TestOnlyFile => "test-only-file",
+            Category::UnreferencedFile => "unreferenced-file",
+            Category::BrokenReference => "broken-reference",
+            Category::CaseMismatch => "case-mismatch",
+            Category::PartMismatch => "part-mismatch",
+            Category::OrphanedPart => "orphaned-part",
+            Category::StaleGenerated => "stale-generated",
+        }
+    }
+
+    /// Longer description of what the category reports
+    pub fn description(&self) -> &'static str {
+        match self {
+            Category::UnreferencedAsset => {
+                "Asset registered in pubspec.yaml but not referenced by any reachable file"
+            }
+            Category::UnregisteredAsset => {
+                "File in an asset directory that isn't registered in pubspec.yaml"
+            }
+            Category::UnusedDependency => "Dependency that isn't used by any reachable file",
+            Category::UnusedLabel => "ARB key that isn't used by any reachable file",
+            Category::UnusedLocator => "Class registered in the locator but never retrieved",
+            Category::TestOnlyFile => "Dart file in lib/ that is only reachable from tests",
+            Category::UnreferencedFile => {
+                "Dart file in lib/ that isn't reachable from any entry point or test"
+            }
+            Category::BrokenReference => "Import, export or part of a file that doesn't exist",
+            Category::CaseMismatch => {
+                "Import, export or part whose path differs in case from the file, which breaks on case sensitive file systems"
+            }
+            Category::PartMismatch => {
+                "Part file whose part of directive names another library than the one including it"
+            }
+            Category::OrphanedPart => "File with a part of directive that no library includes",
+            Category::StaleGenerated => {
+                "Generated file whose source file is gone or no longer includes it"
+            }
+        }
+    }
+
+    /// The check that finds this category
+    pub fn check(&self) -> Check {
+        match self {
+            Category::UnreferencedAsset | Category::UnregisteredAsset => Check::Assets,
+            Category::UnusedDependency => Check::Deps,
+            Category::UnusedLabel => Check::Labels,
+            Category::UnusedLocator => Check::Loc,
+            Category::TestOnlyFile
+            | Category::UnreferencedFile
+            | Category::BrokenReference
+            | Category::CaseMismatch
+            | Category::PartMismatch
+            | Category::OrphanedPart
+            | Category::StaleGenerated => Check::Files,
+        }
+    }
+
+    /// Human readable title of the category
+    pub fn title(&self) -> &'static str {
+        match self {
+            Category::UnreferencedAsset => "Unreferenced registered asset",
+            Category::UnregisteredAsset => "Unregistered asset",
+            Category::UnusedDependency => "Unused dependency",
+            Category::UnusedLabel => "Unreferenced localisation key",
+            Category::UnusedLocator => "Unused locator",
+            Category::TestOnlyFile => "Only referenced from tests",
+            Category::UnreferencedFile => "Unreferenced file",
+            Category::BrokenReference => "Broken reference",
+            Category::CaseMismatch => "Case mismatch",
+            Category::PartMismatch => "Part of another library",
+            Category::OrphanedPart => "Orphaned part",
+            Category::StaleGenerated => "Stale generated file",
+        }
+    }
+}
+
+/// A single unused item found in the project
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct Finding {
+    pub category: Category,
+    pub severity: Severity,
+    /// File the finding applies to, relative to the project root
+    pub path: PathBuf,
+    /// Name of the item for findings within a file, e.g. the dependency or the ARB key
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+    /// Line of the item in the file, starting at 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line: Option<usize>,
+    /// Why the item is reported
+    pub evidence: String,
+    /// The entry points or tests the item is reachable from
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub reached_from: Vec<PathBuf>,
+    /// The file a reference resolves to, relative to the project root
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub target: Option<PathBuf>,
+}
+
+impl Finding {
+    pub fn new(category: Category, path: PathBuf, evidence: &str) -> Self {
+        Self {
+            category,
+            severity: Severity::Error,
+            path,
+            name: None,
+            line: None,
+            evidence: evidence.to_string(),
+            reached_from: Vec::new(),
+            target: None,
+        }
+    }
+
+    pub fn with_name(mut self, name: &str) -> Self {
+        self.name = Some(name.to_string());
+        self
+    }
+
+    pub fn with_line(mut self, line: Option<usize>) -> Self {
+        self.line = line;
+        self
+    }
+
+    pub fn with_reached_from(mut self, reached_from: Vec<PathBuf>) -> Self {
+        self.reached_from = reached_from;
+        self
+    }
+
+    pub fn with_target(mut self, target: PathBuf) -> Self {
+        self.target = Some(target);
+        self
+    }
+
+    /// The item being reported, the name if it has one otherwise the path
+    pub fn subject(&self) -> String {
+        match &self.name {
+            Some(name) => name.clone(),
+            None => self.path.display().to_string(),
+        }
+    }
+}
+
+/// The result of analysing a project
+#[derive(Debug, Default, Clone, Serialize)]
+pub struct AnalysisReport {
+    /// Name of the package from the pubspec
+    pub project: String,
+    /// Entry points the app was walked from
+    pub entries: Vec<PathBuf>,
+    /// Test files the tests were walked from
+    pub tests: Vec<PathBuf>,
+    /// Number of Dart files in `lib/` reachable from the app
+    pub reachable_files: usize,
+    /// The categories that were checked
+    pub checked: Vec<Category>,
+    /// Number of findings left out because they are in the baseline
+    pub baselined: usize,
+    /// Baseline entries that are no longer found
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub fixed: Vec<String>,
+    pub findings: Vec<Finding>,
+}
+
+impl AnalysisReport {
+    /// Gets the findings of the given category
+    pub fn findings_in(&self, category: Category) -> impl Iterator<Item = &Finding> {
+        self.findings.iter().filter(move |x| x.category == category)
+    }
+
+    /// Number of findings of the given category
+    pub fn count(&self, category: Category) -> usize {
+        self.findings_in(category).count()
+    }
+
+    /// Whether any of the findings should fail the run
+    pub fn has_errors(&self) -> bool {
+        self.findings.iter().any(|x| x.severity == Severity::Error)
+    }
+
+    /// Logs the findings grouped by category
+    pub fn log(&self) {
+        log::info!(
+            "{} lib file(s) reachable from the app, {} reachable only from tests, {} unreachable",
+            self.reachable_files,
+            self.count(Category::TestOnlyFile),
+            self.count(Category::UnreferencedFile)
+        );
+        log::info!("");
+        if self.baselined > 0 {
+            log::info!("{} finding(s) left out by the baseline", self.baselined);
+        }
+        for (ind, fixed) in self.fixed.iter().enumerate() {
+            log::info!("{}. Fixed since the baseline: {}", ind + 1, fixed);
+        }
+        if self.baselined > 0 || !self.fixed.is_empty() {
+            log::info!("");
+        }
+        for category in Category::ALL {
+            let mut empty = true;
+            for (ind, finding) in self.findings_in(category).enumerate() {
+                empty = false;
+                let level = match finding.severity {
+                    Severity::Error => log::Level::Error,
+                    Severity::Warning => log::Level::Warn,
+                    Severity::Info | Severity::Off => log::Level::Info,
+                };
+                if let Some(target) = &finding.target {
+                    log::log!(
+                        level,
+                        "{}. {}: {:?}{}: {} ({:?})",
+                        ind + 1,
+                        category.title(),
+                        finding.path,
+                        finding
+                            .line
+                            .map(|x| format!(" line {}", x))
+                            .unwrap_or_default(),
+                        finding.evidence,
+                        target
+                    );
+                } else if finding.reached_from.is_empty() {
+                    log::log!(
+                        level,
+                        "{}. {}: {:?}",
+                        ind + 1,
+                        category.title(),
+                        finding.subject()
+                    );
+                } else {
+                    log::log!(
+                        level,
+                        "{}. {}: {:?} (reached from {})",
+                        ind + 1,
+                        category.title(),
+                        finding.subject(),
+                        finding
+                            .reached_from
+                            .iter()
+                            .map(|x| format!("{:?}", x))
+                            .collect::<Vec<_>>()
+                            .join(", ")
+                    );
+                }
+            }
+            if !empty {
+                log::info!("");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_findings_in() {
+        let report = AnalysisReport {
+            findings: vec![
+                Finding::new(Category::UnreferencedFile, PathBuf::from("lib/a.dart"), ""),
+                Finding::new(
+                    Category::UnusedDependency,
+                    PathBuf::from("pubspec.yaml"),
+                    "",
+                )
+                .with_name("http"),
+                Finding::new(Category::UnreferencedFile, PathBuf::from("lib/b.dart"), ""),
+            ],
+            ..Default::default()
+        };
+        assert_eq!(report.count(Category::UnreferencedFile), 2);
+        assert_eq!(report.count(Category::UnusedDependency), 1);
+        assert_eq!(report.count(Category::UnusedLabel), 0);
+    }
+
+    #[test]
+    fn test_has_errors() {
+        let mut report = AnalysisReport {
+            findings: vec![Finding::new(
+                Category::UnreferencedFile,
+                PathBuf::from("lib/a.dart"),
+                "",
+            )],
+            ..Default::default()
+        };
+        assert!(report.has_errors());
+        report.findings[0].severity = Severity::Warning;
+        assert!(!report.has_errors());
+    }
+
+    #[test]
+    fn test_subject() {
+        let file = Finding::new(Category::UnreferencedFile, PathBuf::from("lib/a.dart"), "");
+        assert_eq!(file.subject(), "lib/a.dart");
+        let dep = Finding::new(
+            Category::UnusedDependency,
+            PathBuf::from("pubspec.yaml"),
+            "",
+        )
+        .with_name("http");
+        assert_eq!(dep.subject(), "http");
+    }
+}
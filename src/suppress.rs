@@ -0,0 +1,132 @@
+//! Inline comments that mark items as intentionally unused.
+//!
+//! - `// dart-unused:ignore-file` before any code in a Dart file keeps the file
+//! - `// dart-unused:ignore` on the line of an item, or the line above it, keeps the item.
+//!   A check can be named to only ignore that check, e.g. `// dart-unused:ignore locator`
+//! - `"@key": {"x-dart-unused": "keep"}` in the ARB metadata keeps the key
+
+use serde_json::Value;
+
+const IGNORE: &str = "dart-unused:ignore";
+const IGNORE_FILE: &str = "dart-unused:ignore-file";
+/// Key in the ARB metadata of a label
+const ARB_KEY: &str = "x-dart-unused";
+
+/// Whether the Dart file has an ignore-file comment before any code
+pub fn is_file_ignored(contents: &str) -> bool {
+    let mut in_block = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.contains(IGNORE_FILE) && (in_block || is_comment(line)) {
+            return true;
+        }
+        if in_block || line.starts_with("/*") {
+            in_block = !line.contains("*/");
+        } else if !line.is_empty() && !is_comment(line) {
+            return false;
+        }
+    }
+    false
+}
+
+/// Whether the line has an ignore comment for any of the names given, either at the end of the
+/// line or on its own on the line above.
+///
+/// The line starts at 1, an ignore comment without names ignores every check.
+pub fn is_line_ignored(contents: &str, line: usize, names: &[&str]) -> bool {
+    if line == 0 {
+        return false;
+    }
+    let mut lines = contents.lines().skip(line - 1);
+    if lines.next().is_some_and(|x| ignores(x, names)) {
+        return true;
+    }
+    line > 1
+        && contents.lines().nth(line - 2).is_some_and(|x| {
+            let x = x.trim_start();
+            (x.starts_with("//") || x.starts_with('#')) && ignores(x, names)
+        })
+}
+
+/// Whether the ARB metadata of a key asks to keep it
+pub fn is_arb_key_kept(metadata: &Value) -> bool {
+    metadata.get(ARB_KEY).and_then(Value::as_str) == Some("keep")
+}
+
+fn ignores(line: &str, names: &[&str]) -> bool {
+    let Some(start) = line.find(IGNORE) else {
+        return false;
+    };
+    let before = &line[..start];
+    if !before.contains("//") && !before.contains('#') {
+        return false;
+    }
+    let rest = &line[start + IGNORE.len()..];
+    if rest.starts_with('-') {
+        return false;
+    }
+    let mut checks = rest
+        .split(|c: char| c.is_whitespace() || c == ',')
+        .filter(|x| !x.is_empty())
+        .peekable();
+    checks.peek().is_none() || checks.any(|check| names.contains(&check))
+}
+
+fn is_comment(line: &str) -> bool {
+    line.starts_with("//") || line.starts_with("/*") || line.starts_with('*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_file_ignored() {
+        assert!(is_file_ignored(
+            "// dart-unused:ignore-file\nimport 'a.dart';"
+        ));
+        assert!(is_file_ignored(
+            "// Copyright\n\n// dart-unused:ignore-file\nclass A {}"
+        ));
+        assert!(is_file_ignored(
+            "/*\n * dart-unused:ignore-file\n */\nclass A {}"
+        ));
+        assert!(!is_file_ignored("class A {}\n// dart-unused:ignore-file"));
+        assert!(!is_file_ignored("// dart-unused:ignore\nclass A {}"));
+        assert!(!is_file_ignored("final a = 'dart-unused:ignore-file';"));
+    }
+
+    #[test]
+    fn test_line_ignored() {
+        let contents = r#"
+  locator.registerFactory<A>(() => A()); // dart-unused:ignore locator
+  // dart-unused:ignore
+  locator.registerFactory<B>(() => B());
+  // dart-unused:ignore deps
+  locator.registerFactory<C>(() => C());
+  locator.registerFactory<D>(() => D());
+"#;
+        assert!(is_line_ignored(contents, 2, &["locator"]));
+        assert!(is_line_ignored(contents, 4, &["locator"]));
+        assert!(!is_line_ignored(contents, 6, &["locator"]));
+        assert!(is_line_ignored(contents, 6, &["deps"]));
+        assert!(!is_line_ignored(contents, 7, &["locator"]));
+    }
+
+    #[test]
+    fn test_yaml_line_ignored() {
+        let contents = "dependencies:\n  http: ^1.0.0 # dart-unused:ignore\n  path: ^1.0.0\n";
+        assert!(is_line_ignored(contents, 2, &["deps"]));
+        assert!(!is_line_ignored(contents, 3, &["deps"]));
+        assert!(!is_line_ignored(contents, 1, &["deps"]));
+    }
+
+    #[test]
+    fn test_arb_key_kept() {
+        let metadata: Value =
+            serde_json::from_str(r#"{"description": "A", "x-dart-unused": "keep"}"#).unwrap();
+        assert!(is_arb_key_kept(&metadata));
+        let metadata: Value = serde_json::from_str(r#"{"description": "A"}"#).unwrap();
+        assert!(!is_arb_key_kept(&metadata));
+    }
+}
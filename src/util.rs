@@ -1,14 +1,21 @@
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+
+use glob::Pattern;
 
 /// Sets the current working directory to the given path.
 pub fn set_current_dir(path: &PathBuf) -> anyhow::Result<()> {
     if !path.exists() {
-        panic!("Path does not exist");
+        return Err(anyhow::anyhow!("Path {:?} does not exist", path));
     } else if path.is_dir() {
-        std::env::set_current_dir(path)?;
+        std::env::set_current_dir(path)
+            .map_err(|e| anyhow::anyhow!("Failed to change to {:?}: {}", path, e))?;
     } else {
-        let parent = path.parent().expect("Failed to get parent directory");
-        std::env::set_current_dir(parent)?;
+        // a file name on its own is already in the current directory
+        let parent = path.parent().filter(|x| !x.as_os_str().is_empty());
+        if let Some(parent) = parent {
+            std::env::set_current_dir(parent)
+                .map_err(|e| anyhow::anyhow!("Failed to change to {:?}: {}", parent, e))?;
+        }
     }
     Ok(())
 }
@@ -28,3 +35,21 @@ pub fn get_dart_command_path() -> anyhow::Result<String> {
     }
     Ok(l.last().expect("Failed to get dart command").to_string())
 }
+
+/// Finds the first line matching the predicate, returning its number starting at 1.
+pub fn find_line(contents: &str, predicate: impl Fn(&str) -> bool) -> Option<usize> {
+    contents.lines().position(predicate).map(|index| index + 1)
+}
+
+/// Compiles the glob patterns, failing on the first invalid one.
+pub fn compile_patterns(patterns: &[String]) -> anyhow::Result<Vec<Pattern>> {
+    patterns
+        .iter()
+        .map(|x| Pattern::new(x).map_err(|e| anyhow::anyhow!("Invalid glob {:?}: {}", x, e)))
+        .collect()
+}
+
+/// Checks if the path matches any of the patterns.
+pub fn matches_any(patterns: &[Pattern], path: &Path) -> bool {
+    patterns.iter().any(|x| x.matches_path(path))
+}